@@ -53,4 +53,9 @@ impl MonitorId {
         };
         dimension
     }
+
+    #[inline]
+    pub fn get_video_modes(&self) -> Vec<::VideoMode> {
+        Vec::new()
+    }
 }
@@ -88,6 +88,11 @@ impl MonitorId {
     pub fn get_dimensions(&self) -> (u32, u32) {
         unimplemented!()
     }
+
+    #[inline]
+    pub fn get_video_modes(&self) -> Vec<::VideoMode> {
+        Vec::new()
+    }
 }
 
 impl Window {
@@ -229,6 +234,68 @@ impl Window {
     pub fn set_cursor_position(&self, x: i32, y: i32) -> Result<(), ()> {
         Ok(())
     }
+
+    #[inline]
+    pub fn get_clipboard(&self, _mime_type: &str) -> Option<Vec<u8>> {
+        None
+    }
+
+    #[inline]
+    pub fn set_clipboard(&self, _mime_type: &str, _data: Vec<u8>) {
+    }
+
+    #[inline]
+    pub fn get_primary_selection(&self, _mime_type: &str) -> Option<Vec<u8>> {
+        None
+    }
+
+    #[inline]
+    pub fn set_primary_selection(&self, _mime_type: &str, _data: Vec<u8>) {
+    }
+
+    #[inline]
+    pub fn start_drag(&self, _contents: Vec<(String, Vec<u8>)>) {
+    }
+
+    #[inline]
+    pub fn set_min_dimensions(&self, _dimensions: Option<(u32, u32)>) {
+    }
+
+    #[inline]
+    pub fn set_max_dimensions(&self, _dimensions: Option<(u32, u32)>) {
+    }
+
+    #[inline]
+    pub fn set_fullscreen(&self, _monitor: Option<MonitorId>, _video_mode: Option<::VideoMode>) {
+    }
+
+    #[inline]
+    pub fn set_maximized(&self, _maximized: bool) {
+    }
+
+    #[inline]
+    pub fn set_minimized(&self, _minimized: bool) {
+    }
+
+    #[inline]
+    pub fn restore(&self) {
+    }
+
+    #[inline]
+    pub fn set_decorations(&self, _decorations: bool) {
+    }
+
+    #[inline]
+    pub fn set_window_icon(&self, _icon: Option<&::Icon>) {
+    }
+
+    #[inline]
+    pub fn set_ime_allowed(&self, _allowed: bool) {
+    }
+
+    #[inline]
+    pub fn set_ime_position(&self, _x: i32, _y: i32) {
+    }
 }
 
 impl GlContext for Window {
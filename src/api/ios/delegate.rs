@@ -133,7 +133,10 @@ pub fn create_delegate_class() {
                         3 => TouchPhase::Ended,
                         4 => TouchPhase::Cancelled,
                         _ => panic!("unexpected touch phase: {:?}", phase)
-                    }
+                    },
+                    force: None,
+                    contact: None,
+                    device_id: None,
                 }));
             }
         }
@@ -2,6 +2,7 @@ pub mod android;
 pub mod cocoa;
 pub mod dlopen;
 pub mod emscripten;
+pub mod uri_list;
 pub mod wayland;
 pub mod win32;
 pub mod x11;
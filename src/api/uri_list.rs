@@ -0,0 +1,98 @@
+#![cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "openbsd"))]
+
+//! Decoding of the `text/uri-list` format that is used to drag and drop files.
+
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+use std::str;
+
+/// MIME type of the format.
+pub const MIME_TYPE: &'static str = "text/uri-list";
+
+/// Returns the local files in a list of URIs, ignoring the other URIs.
+pub fn decode(data: &[u8]) -> Vec<PathBuf> {
+    data.split(|&b| b == b'\n')
+        .map(|line| if line.ends_with(b"\r") { &line[..line.len() - 1] } else { line })
+        .filter(|line| !line.is_empty() && line[0] != b'#')
+        .filter_map(file_path)
+        .collect()
+}
+
+fn file_path(uri: &[u8]) -> Option<PathBuf> {
+    let prefix = b"file://";
+    if !uri.starts_with(prefix) {
+        return None;
+    }
+
+    // skip the host name, which is usually empty or `localhost`
+    let uri = &uri[prefix.len()..];
+    let path = match uri.iter().position(|&b| b == b'/') {
+        Some(start) => &uri[start..],
+        None => return None
+    };
+
+    percent_decode(path).map(|path| PathBuf::from(OsStr::from_bytes(&path)))
+}
+
+fn percent_decode(input: &[u8]) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len());
+    let mut i = 0;
+
+    while i < input.len() {
+        if input[i] == b'%' {
+            if i + 3 > input.len() {
+                return None;
+            }
+            let byte = str::from_utf8(&input[i + 1..i + 3]).ok()
+                                                          .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            match byte {
+                Some(byte) => output.push(byte),
+                None => return None
+            }
+            i += 3;
+        } else {
+            output.push(input[i]);
+            i += 1;
+        }
+    }
+
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use super::decode;
+
+    #[test]
+    fn decode_file_uris() {
+        let data = b"# dropped from a file manager\r\n\
+                     file:///home/user/a.txt\r\n\
+                     file://localhost/tmp/b%20c.png\r\n\
+                     http://example.com/d.txt\r\n\
+                     \r\n\
+                     file:///e";
+        assert_eq!(decode(data), vec![PathBuf::from("/home/user/a.txt"),
+                                      PathBuf::from("/tmp/b c.png"),
+                                      PathBuf::from("/e")]);
+    }
+
+    #[test]
+    fn decode_invalid_uris() {
+        assert!(decode(b"file://").is_empty());
+        assert!(decode(b"file://host").is_empty());
+        assert!(decode(b"file:///a%2").is_empty());
+        assert!(decode(b"file:///a%zzb").is_empty());
+        assert!(decode(b"").is_empty());
+    }
+
+    #[test]
+    fn decode_non_utf8_path() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        assert_eq!(decode(b"file:///a%FF%2Fb\n"),
+                   vec![PathBuf::from(OsStr::from_bytes(b"/a\xFF/b"))]);
+    }
+}
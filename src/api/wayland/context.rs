@@ -1,21 +1,37 @@
 use Event as GlutinEvent;
+use VideoMode;
 
 use std::collections::{HashMap, VecDeque, HashSet};
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::os::unix::io::AsRawFd;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use libc;
 use libc::c_void;
 
 use wayland_client::{EventIterator, Proxy, ProxyId};
 use wayland_client::wayland::get_display;
 use wayland_client::wayland::compositor::{WlCompositor, WlSurface};
+use wayland_client::wayland::data_device::WlDataDeviceManager;
 use wayland_client::wayland::output::WlOutput;
-use wayland_client::wayland::seat::{WlSeat, WlPointer};
+use wayland_client::wayland::seat::{WlSeat, WlPointer, WlTouch};
 use wayland_client::wayland::shell::{WlShell, WlShellSurface};
 use wayland_client::wayland::shm::WlShm;
 use wayland_client::wayland::subcompositor::WlSubcompositor;
 
+use super::data_device::{ClipboardData, DataDevice};
+use super::gestures::Gestures;
+use super::primary_selection::PrimarySelection;
+use super::protocols::Registry;
+use super::tablet::Tablets;
+use super::text_input::TextInput;
 use super::wayland_kbd::MappedKeyboard;
-use super::wayland_window::DecoratedSurface;
+use super::decorations::DecoratedSurface;
+
+/// Time to wait for the owner of the clipboard to send its contents, in milliseconds.
+const CLIPBOARD_TIMEOUT: u64 = 1000;
 
 lazy_static! {
     pub static ref WAYLAND_CONTEXT: Option<WaylandContext> = {
@@ -28,7 +44,8 @@ wayland_env!(InnerEnv,
     seat: WlSeat,
     shell: WlShell,
     shm: WlShm,
-    subcompositor: WlSubcompositor
+    subcompositor: WlSubcompositor,
+    data_device_manager: WlDataDeviceManager
 );
 
 pub struct WaylandFocuses {
@@ -36,16 +53,30 @@ pub struct WaylandFocuses {
     pub pointer_on: Option<ProxyId>,
     pub pointer_at: Option<(f64, f64)>,
     pub keyboard: Option<MappedKeyboard>,
-    pub keyboard_on: Option<ProxyId>
+    pub keyboard_on: Option<ProxyId>,
+    pub touch: Option<WlTouch>,
+    /// Fingers currently on a surface: (id, surface, last position)
+    pub touch_points: Vec<(i32, ProxyId, (f64, f64))>,
+    /// Serial of the latest input event, required to set the clipboard
+    pub serial: u32,
+    /// Serial of the latest pointer button press, required to start a drag
+    pub button_serial: u32
 }
 
 pub struct WaylandContext {
     inner: InnerEnv,
     iterator: Mutex<EventIterator>,
-    monitors: Vec<(WlOutput, u32, u32, String)>,
+    monitors: Vec<(WlOutput, u32, u32, String, Vec<VideoMode>)>,
     queues: Mutex<HashMap<ProxyId, Arc<Mutex<VecDeque<GlutinEvent>>>>>,
     known_surfaces: Mutex<HashSet<ProxyId>>,
-    focuses: Mutex<WaylandFocuses>
+    /// Ids of our surfaces from their pointers, for the protocols handled through `wayland-sys`
+    surface_ids: Mutex<HashMap<usize, ProxyId>>,
+    focuses: Mutex<WaylandFocuses>,
+    data_device: Mutex<DataDevice>,
+    tablets: Option<Tablets>,
+    gestures: Option<Gestures>,
+    text_input: Option<TextInput>,
+    primary_selection: Option<PrimarySelection>
 }
 
 impl WaylandContext {
@@ -63,26 +94,50 @@ impl WaylandContext {
             .flat_map(|&(id, _, _)| inner_env.rebind_id::<WlOutput>(id))
             .map(|(mut monitor, _)| {
                 monitor.set_evt_iterator(&outputs_events);
-                (monitor, 0, 0, String::new())
+                (monitor, 0, 0, String::new(), Vec::new())
             }).collect();
 
         inner_env.display.sync_roundtrip().unwrap();
 
         super::monitor::init_monitors(&mut monitors, outputs_events);
 
+        let data_device = match (&inner_env.data_device_manager, &inner_env.seat) {
+            (&Some((ref manager, _)), &Some((ref seat, _))) => Some(manager.get_data_device(seat)),
+            _ => None
+        };
+
+        let (tablets, gestures, text_input, primary_selection) = {
+            let registry = unsafe { Registry::new(inner_env.display.ptr() as *mut _, &inner_env.globals) };
+            let seat = inner_env.seat.as_ref().map(|&(ref seat, _)| seat.ptr() as *mut _);
+            (seat.and_then(|seat| Tablets::new(&registry, seat)),
+             Gestures::new(&registry),
+             seat.and_then(|seat| TextInput::new(&registry, seat)),
+             seat.and_then(|seat| PrimarySelection::new(&registry, seat)))
+        };
+
         Some(WaylandContext {
             inner: inner_env,
             iterator: Mutex::new(iterator),
             monitors: monitors,
             queues: Mutex::new(HashMap::new()),
             known_surfaces: Mutex::new(HashSet::new()),
+            surface_ids: Mutex::new(HashMap::new()),
             focuses: Mutex::new(WaylandFocuses {
                 pointer: None,
                 pointer_on: None,
                 pointer_at: None,
                 keyboard: None,
-                keyboard_on: None
-            })
+                keyboard_on: None,
+                touch: None,
+                touch_points: Vec::new(),
+                serial: 0,
+                button_serial: 0
+            }),
+            data_device: Mutex::new(DataDevice::new(data_device)),
+            tablets: tablets,
+            gestures: gestures,
+            text_input: text_input,
+            primary_selection: primary_selection
         })
     }
 
@@ -97,13 +152,19 @@ impl WaylandContext {
             };
             self.queues.lock().unwrap().insert(id, queue.clone());
             self.known_surfaces.lock().unwrap().insert(id);
+            self.surface_ids.lock().unwrap().insert(s.ptr() as usize, id);
             (s, queue)
         })
     }
 
-    pub fn dropped_surface(&self, id: ProxyId) {
+    pub fn dropped_surface(&self, surface: &WlSurface) {
+        let id = surface.id();
         self.queues.lock().unwrap().remove(&id);
         self.known_surfaces.lock().unwrap().remove(&id);
+        self.surface_ids.lock().unwrap().remove(&(surface.ptr() as usize));
+        if let Some(ref text_input) = self.text_input {
+            text_input.forget(surface.ptr() as *mut _);
+        }
     }
 
     pub fn decorated_from(&self, surface: &WlSurface, width: i32, height: i32) -> Option<DecoratedSurface> {
@@ -120,22 +181,16 @@ impl WaylandContext {
         }
     }
 
-    pub fn plain_from(&self, surface: &WlSurface, fullscreen: Option<ProxyId>) -> Option<WlShellSurface> {
-        use wayland_client::wayland::shell::WlShellSurfaceFullscreenMethod;
-
+    pub fn plain_from(&self, surface: &WlSurface, fullscreen: Option<ProxyId>,
+                      video_mode: Option<VideoMode>) -> Option<WlShellSurface>
+    {
         let inner = &self.inner;
         if let Some((ref shell, _)) = inner.shell {
             let shell_surface = shell.get_shell_surface(surface);
             if let Some(monitor_id) = fullscreen {
-                for m in &self.monitors {
-                    if m.0.id() == monitor_id {
-                        shell_surface.set_fullscreen(
-                            WlShellSurfaceFullscreenMethod::Default,
-                            0,
-                            Some(&m.0)
-                        );
-                        return Some(shell_surface)
-                    }
+                if self.monitors.iter().any(|m| m.0.id() == monitor_id) {
+                    self.set_fullscreen(&shell_surface, Some(monitor_id), video_mode);
+                    return Some(shell_surface)
                 }
             }
             shell_surface.set_toplevel();
@@ -145,6 +200,25 @@ impl WaylandContext {
         }
     }
 
+    /// Makes a shell surface fullscreen on a monitor, or on the one the compositor picks.
+    ///
+    /// With a video mode, the compositor is asked to switch the monitor to it, otherwise it
+    /// scales the surface or adds black borders as it prefers.
+    pub fn set_fullscreen(&self, shell_surface: &WlShellSurface, monitor: Option<ProxyId>,
+                          video_mode: Option<VideoMode>)
+    {
+        use wayland_client::wayland::shell::WlShellSurfaceFullscreenMethod;
+
+        let output = monitor.and_then(|id| self.monitors.iter().find(|m| m.0.id() == id))
+                            .map(|m| &m.0);
+        match video_mode {
+            // the framerate is in mHz
+            Some(mode) => shell_surface.set_fullscreen(WlShellSurfaceFullscreenMethod::Driver,
+                                                       mode.refresh_rate * 1000, output),
+            None => shell_surface.set_fullscreen(WlShellSurfaceFullscreenMethod::Default, 0, output),
+        }
+    }
+
     pub fn display_ptr(&self) -> *const c_void {
         self.inner.display.ptr() as *const _
     }
@@ -155,6 +229,7 @@ impl WaylandContext {
         let mut focuses = self.focuses.lock().unwrap();
         let known_surfaces = self.known_surfaces.lock().unwrap();
         let queues = self.queues.lock().unwrap();
+        let mut data_device = self.data_device.lock().unwrap();
         // first, keyboard events
         let kdb_evts = super::keyboard::translate_kbd_events(&mut *focuses, &known_surfaces);
         for (evt, id) in kdb_evts {
@@ -164,15 +239,127 @@ impl WaylandContext {
         }
         // then, the rest
         for evt in &mut *iterator {
-            if let Some((evt, id)) = super::events::translate_event(
+            let mut evts = Vec::new();
+            let evt = data_device.handle_event(evt, &known_surfaces, &mut evts);
+            for (evt, id) in evts {
+                if let Some(q) = queues.get(&id) {
+                    q.lock().unwrap().push_back(evt);
+                }
+            }
+            let evt = match evt {
+                Some(evt) => evt,
+                None => continue
+            };
+            let evts = super::events::translate_event(
                 evt, &mut *focuses, &known_surfaces,
-                self.inner.seat.as_ref().map(|s| &s.0))
-            {
+                self.inner.seat.as_ref().map(|s| &s.0));
+            for (evt, id) in evts {
                 if let Some(q) = queues.get(&id) {
                     q.lock().unwrap().push_back(evt);
                 }
             }
         }
+        // the gestures are received once the pointer exists
+        if let (Some(gestures), Some(pointer)) = (self.gestures.as_ref(), focuses.pointer.as_ref()) {
+            gestures.set_pointer(pointer.ptr() as *mut _);
+        }
+        // then, the pens, the gestures and the input method, whose events were gathered while
+        // dispatching
+        let surface_ids = self.surface_ids.lock().unwrap();
+        let raw_evts = self.tablets.iter().flat_map(|t| t.take_events())
+                           .chain(self.gestures.iter().flat_map(|g| g.take_events()))
+                           .chain(self.text_input.iter().flat_map(|t| t.take_events()));
+        for (evt, surface) in raw_evts {
+            if let Some(q) = surface_ids.get(&(surface as usize)).and_then(|id| queues.get(id)) {
+                q.lock().unwrap().push_back(evt);
+            }
+        }
+        // finally, the files being dragged that could be read
+        let mut drag_evts = Vec::new();
+        data_device.poll_drag(&mut drag_evts);
+        for (evt, id) in drag_evts {
+            if let Some(q) = queues.get(&id) {
+                q.lock().unwrap().push_back(evt);
+            }
+        }
+    }
+
+    pub fn get_clipboard(&self, mime_type: &str) -> Option<Vec<u8>> {
+        let data = self.data_device.lock().unwrap().get(mime_type);
+        match data {
+            Some(ClipboardData::Local(data)) => Some(data),
+            Some(ClipboardData::Remote(pipe)) => {
+                // the owner only gets the request once we flush
+                let _ = self.inner.display.flush();
+                read_pipe(pipe, Duration::from_millis(CLIPBOARD_TIMEOUT))
+            },
+            None => None
+        }
+    }
+
+    pub fn set_clipboard(&self, mime_type: &str, data: Vec<u8>) {
+        if let Some((ref manager, _)) = self.inner.data_device_manager {
+            let serial = self.focuses.lock().unwrap().serial;
+            self.data_device.lock().unwrap().set(manager, serial, mime_type, data);
+            let _ = self.inner.display.flush();
+        }
+    }
+
+    pub fn get_primary_selection(&self, mime_type: &str) -> Option<Vec<u8>> {
+        let data = self.primary_selection.as_ref().and_then(|selection| selection.get(mime_type));
+        match data {
+            Some(ClipboardData::Local(data)) => Some(data),
+            Some(ClipboardData::Remote(pipe)) => {
+                // the owner only gets the request once we flush
+                let _ = self.inner.display.flush();
+                read_pipe(pipe, Duration::from_millis(CLIPBOARD_TIMEOUT))
+            },
+            None => None
+        }
+    }
+
+    pub fn set_primary_selection(&self, mime_type: &str, data: Vec<u8>) {
+        if let Some(ref selection) = self.primary_selection {
+            let serial = self.focuses.lock().unwrap().serial;
+            selection.set(serial, mime_type, data);
+            let _ = self.inner.display.flush();
+        }
+    }
+
+    pub fn set_ime_allowed(&self, surface: &WlSurface, allowed: bool) {
+        if let Some(ref text_input) = self.text_input {
+            text_input.set_allowed(surface.ptr() as *mut _, allowed);
+            let _ = self.inner.display.flush();
+        }
+    }
+
+    pub fn set_ime_position(&self, surface: &WlSurface, x: i32, y: i32) {
+        if let Some(ref text_input) = self.text_input {
+            text_input.set_caret(surface.ptr() as *mut _, x, y);
+            let _ = self.inner.display.flush();
+        }
+    }
+
+    pub fn start_drag(&self, origin: &WlSurface, contents: Vec<(String, Vec<u8>)>) {
+        let mut evts = Vec::new();
+        let started = match self.inner.data_device_manager {
+            Some((ref manager, _)) => {
+                let serial = self.focuses.lock().unwrap().button_serial;
+                self.data_device.lock().unwrap().start_drag(manager, serial, origin, contents, &mut evts)
+            },
+            None => false
+        };
+        if !started {
+            evts.push((GlutinEvent::DragCancelled, origin.id()));
+        }
+        let _ = self.inner.display.flush();
+
+        let queues = self.queues.lock().unwrap();
+        for (evt, id) in evts {
+            if let Some(q) = queues.get(&id) {
+                q.lock().unwrap().push_back(evt);
+            }
+        }
     }
 
     pub fn flush_events(&self) -> ::std::io::Result<i32> {
@@ -208,4 +395,42 @@ impl WaylandContext {
         }
         None
     }
+
+    pub fn monitor_video_modes(&self, pid: ProxyId) -> Vec<VideoMode> {
+        self.monitors.iter()
+                     .find(|o| o.0.id() == pid)
+                     .map(|o| o.4.clone())
+                     .unwrap_or_else(Vec::new)
+    }
+}
+
+/// Reads a pipe until the other end is closed, or returns `None` if the writer doesn't finish
+/// before the timeout.
+fn read_pipe(mut pipe: File, timeout: Duration) -> Option<Vec<u8>> {
+    let start = Instant::now();
+    let mut data = Vec::new();
+    let mut buffer = [0; 4096];
+
+    loop {
+        let elapsed = start.elapsed();
+        if elapsed > timeout {
+            return None;
+        }
+        let remaining = timeout - elapsed;
+        let remaining = remaining.as_secs() * 1000 + remaining.subsec_nanos() as u64 / 1_000_000;
+
+        let mut fd = libc::pollfd { fd: pipe.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+        match unsafe { libc::poll(&mut fd, 1, remaining as libc::c_int) } {
+            0 => return None,
+            count if count < 0 => continue,
+            _ => ()
+        }
+
+        match pipe.read(&mut buffer) {
+            Ok(0) => return Some(data),
+            Ok(count) => data.extend_from_slice(&buffer[..count]),
+            Err(ref err) if err.kind() == ErrorKind::Interrupted => (),
+            Err(_) => return None
+        }
+    }
 }
@@ -0,0 +1,376 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::io::FromRawFd;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::{mem, thread};
+
+use libc;
+
+use Event as GlutinEvent;
+use api::uri_list;
+
+use wayland_client::{Proxy, ProxyId};
+use wayland_client::Event as WaylandEvent;
+use wayland_client::wayland::WaylandProtocolEvent as WPE;
+use wayland_client::wayland::compositor::WlSurface;
+use wayland_client::wayland::data_device::{WlDataDevice, WlDataDeviceEvent, WlDataDeviceManager, WlDataOffer,
+                                           WlDataOfferEvent, WlDataSource, WlDataSourceEvent};
+
+/// MIME type used for text by the public API.
+pub const TEXT_MIME_TYPE: &'static str = "text/plain;charset=utf-8";
+
+/// Name that X11 clients use for UTF-8 text, which XWayland forwards as is.
+const X11_TEXT_MIME_TYPE: &'static str = "UTF8_STRING";
+
+/// Contents of the clipboard, either ours or to be read from another client.
+pub enum ClipboardData {
+    Local(Vec<u8>),
+    /// Read end of a pipe the other client writes the data into, the display must be flushed
+    /// before reading from it.
+    Remote(File),
+}
+
+/// A drag that is currently over one of our surfaces.
+struct Drag {
+    offer: ProxyId,
+    surface: ProxyId,
+    /// Non-blocking pipe the source writes the list of files into, until it is read completely
+    pipe: Option<File>,
+    data: Vec<u8>,
+    paths: Option<Vec<PathBuf>>,
+    dropped: bool,
+}
+
+/// A drag started from one of our surfaces.
+struct OutgoingDrag {
+    source: WlDataSource,
+    /// Data for every MIME type
+    contents: Vec<(String, Arc<Vec<u8>>)>,
+    surface: ProxyId,
+}
+
+/// Tracks the clipboard and the drags over our surfaces through `wl_data_device`.
+pub struct DataDevice {
+    device: Option<WlDataDevice>,
+    /// Offers announced by the compositor, with the MIME types they provide
+    offers: Vec<(WlDataOffer, Vec<String>)>,
+    /// Offer that currently holds the contents of the clipboard
+    selection: Option<ProxyId>,
+    /// Our source if we own the clipboard, with the data for every MIME type
+    source: Option<(WlDataSource, Vec<(String, Arc<Vec<u8>>)>)>,
+    drag: Option<Drag>,
+    outgoing: Option<OutgoingDrag>,
+}
+
+impl DataDevice {
+    pub fn new(device: Option<WlDataDevice>) -> DataDevice {
+        DataDevice {
+            device: device,
+            offers: Vec::new(),
+            selection: None,
+            source: None,
+            drag: None,
+            outgoing: None,
+        }
+    }
+
+    /// Processes the data device events, and gives back the events unrelated to it.
+    pub fn handle_event(&mut self, evt: WaylandEvent, known_surfaces: &HashSet<ProxyId>,
+                        out: &mut Vec<(GlutinEvent, ProxyId)>) -> Option<WaylandEvent>
+    {
+        let WaylandEvent::Wayland(wayland_evt) = evt;
+        match wayland_evt {
+            WPE::WlDataDevice(_, WlDataDeviceEvent::DataOffer(offer)) => {
+                self.offers.push((offer, Vec::new()));
+            },
+            WPE::WlDataDevice(_, WlDataDeviceEvent::Selection(offer_id)) => {
+                // the offer is null when the clipboard is empty, which matches none of ours
+                self.selection = Some(offer_id);
+                self.destroy_unused_offers();
+            },
+            WPE::WlDataDevice(_, WlDataDeviceEvent::Enter(serial, surface, _, _, offer_id)) => {
+                self.drag = None;
+                // a null offer is a drag within another client, `accept_drag` ignores it
+                if known_surfaces.contains(&surface) {
+                    self.accept_drag(serial, surface, offer_id);
+                }
+                self.destroy_unused_offers();
+            },
+            WPE::WlDataDevice(_, WlDataDeviceEvent::Leave) => {
+                // the source keeps writing after a drop even if the drag leaves
+                if self.drag.as_ref().map_or(false, |drag| !drag.dropped) {
+                    let drag = self.drag.take().unwrap();
+                    if drag.paths.as_ref().map_or(false, |paths| !paths.is_empty()) {
+                        out.push((GlutinEvent::HoveredFileCancelled, drag.surface));
+                    }
+                    self.destroy_unused_offers();
+                }
+            },
+            WPE::WlDataDevice(_, WlDataDeviceEvent::Drop) => {
+                if let Some(ref mut drag) = self.drag {
+                    drag.dropped = true;
+                }
+                self.finish_drag(out);
+            },
+            WPE::WlDataOffer(id, WlDataOfferEvent::Offer(mime_type)) => {
+                if let Some(&mut (_, ref mut mime_types)) = self.offers.iter_mut().find(|o| o.0.id() == id) {
+                    mime_types.push(mime_type);
+                }
+            },
+            WPE::WlDataSource(id, WlDataSourceEvent::Send(mime_type, fd)) => {
+                let data = {
+                    let clipboard = self.source.as_ref().map(|&(ref source, ref contents)| (source.id(), contents));
+                    let dragged = self.outgoing.as_ref().map(|drag| (drag.source.id(), &drag.contents));
+                    clipboard.into_iter()
+                             .chain(dragged)
+                             .find(|&(source, _)| source == id)
+                             .and_then(|(_, contents)| contents.iter().find(|c| c.0 == mime_type))
+                             .map(|c| c.1.clone())
+                };
+                let mut file = unsafe { File::from_raw_fd(fd) };
+                if let Some(data) = data {
+                    // don't block the event loop while the other client reads
+                    thread::spawn(move || { let _ = file.write_all(&data); });
+                }
+
+                // the source has no event at the end of the drop before version 3 of the
+                // protocol, the target asking for the data is the best hint we get
+                if self.outgoing.as_ref().map(|drag| drag.source.id()) == Some(id) {
+                    self.end_outgoing_drag(GlutinEvent::DragDropped, out);
+                }
+            },
+            WPE::WlDataSource(id, WlDataSourceEvent::Cancelled) if self.outgoing.as_ref().map(|drag| drag.source.id()) == Some(id) => {
+                self.end_outgoing_drag(GlutinEvent::DragCancelled, out);
+            },
+            WPE::WlDataSource(id, WlDataSourceEvent::Cancelled) => {
+                // another client took over the clipboard
+                if self.source.as_ref().map(|s| s.0.id()) == Some(id) {
+                    if let Some((source, _)) = self.source.take() {
+                        source.destroy();
+                    }
+                }
+            },
+            WPE::WlDataDevice(..) | WPE::WlDataSource(..) => (),
+            other => return Some(WaylandEvent::Wayland(other))
+        }
+        None
+    }
+
+    /// Reads what is available of the list of files being dragged, without blocking.
+    pub fn poll_drag(&mut self, out: &mut Vec<(GlutinEvent, ProxyId)>) {
+        let mut finished = false;
+        if let Some(Drag { pipe: Some(ref mut pipe), ref mut data, .. }) = self.drag {
+            let mut buffer = [0; 4096];
+            loop {
+                match pipe.read(&mut buffer) {
+                    Ok(0) => {
+                        finished = true;
+                        break;
+                    },
+                    Ok(count) => data.extend_from_slice(&buffer[..count]),
+                    Err(ref err) if err.kind() == ErrorKind::Interrupted => (),
+                    Err(ref err) if err.kind() == ErrorKind::WouldBlock => break,
+                    Err(_) => {
+                        data.clear();
+                        finished = true;
+                        break;
+                    }
+                }
+            }
+        }
+
+        if finished {
+            {
+                let drag = self.drag.as_mut().unwrap();
+                drag.pipe = None;
+                let paths = uri_list::decode(&drag.data);
+                if !drag.dropped {
+                    out.extend(paths.iter().cloned().map(|path| (GlutinEvent::HoveredFile(path), drag.surface)));
+                }
+                drag.paths = Some(paths);
+            }
+            self.finish_drag(out);
+        }
+    }
+
+    /// Returns the contents of the clipboard in the given MIME type.
+    pub fn get(&self, mime_type: &str) -> Option<ClipboardData> {
+        let mime_types = mime_types_for(mime_type);
+
+        if let Some((_, ref contents)) = self.source {
+            return mime_types.iter()
+                             .filter_map(|mime_type| contents.iter().find(|c| c.0 == *mime_type))
+                             .map(|c| ClipboardData::Local((*c.1).clone()))
+                             .next();
+        }
+
+        let &(ref offer, ref offered) = match self.offers.iter().find(|o| Some(o.0.id()) == self.selection) {
+            Some(offer) => offer,
+            None => return None
+        };
+        let mime_type = match mime_types.into_iter().find(|m| offered.contains(m)) {
+            Some(mime_type) => mime_type,
+            None => return None
+        };
+
+        receive(offer, mime_type).map(ClipboardData::Remote)
+    }
+
+    /// Replaces the contents of the clipboard. `serial` must be the serial of a recent input
+    /// event, otherwise the compositor ignores the request.
+    pub fn set(&mut self, manager: &WlDataDeviceManager, serial: u32, mime_type: &str, data: Vec<u8>) {
+        let device = match self.device {
+            Some(ref device) => device,
+            None => return
+        };
+
+        let data = Arc::new(data);
+        let source = manager.create_data_source();
+        let contents: Vec<_> = mime_types_for(mime_type).into_iter()
+                                                        .map(|mime_type| (mime_type, data.clone()))
+                                                        .collect();
+        for &(ref mime_type, _) in &contents {
+            source.offer(mime_type.clone());
+        }
+        device.set_selection(Some(&source), serial);
+
+        if let Some((old_source, _)) = self.source.take() {
+            old_source.destroy();
+        }
+        self.source = Some((source, contents));
+    }
+
+    /// Starts dragging data in several MIME types from one of our surfaces. `serial` must be the
+    /// serial of the pointer button press that starts the drag.
+    ///
+    /// Returns false if the drag could not be started.
+    pub fn start_drag(&mut self, manager: &WlDataDeviceManager, serial: u32, origin: &WlSurface,
+                      contents: Vec<(String, Vec<u8>)>, out: &mut Vec<(GlutinEvent, ProxyId)>)
+                      -> bool
+    {
+        let device = match self.device {
+            Some(ref device) => device,
+            None => return false
+        };
+
+        let source = manager.create_data_source();
+        let mut offered: Vec<(String, Arc<Vec<u8>>)> = Vec::new();
+        for (mime_type, data) in contents {
+            let data = Arc::new(data);
+            for mime_type in mime_types_for(&mime_type) {
+                if !offered.iter().any(|o| o.0 == mime_type) {
+                    source.offer(mime_type.clone());
+                    offered.push((mime_type, data.clone()));
+                }
+            }
+        }
+        device.start_drag(Some(&source), origin, None, serial);
+
+        // a previous drag whose end was never reported is over anyway
+        self.end_outgoing_drag(GlutinEvent::DragCancelled, out);
+        self.outgoing = Some(OutgoingDrag {
+            source: source,
+            contents: offered,
+            surface: origin.id(),
+        });
+        true
+    }
+
+    fn end_outgoing_drag(&mut self, event: GlutinEvent, out: &mut Vec<(GlutinEvent, ProxyId)>) {
+        if let Some(drag) = self.outgoing.take() {
+            drag.source.destroy();
+            out.push((event, drag.surface));
+        }
+    }
+
+    /// Accepts a drag that entered one of our surfaces if it contains files, and starts
+    /// reading them.
+    fn accept_drag(&mut self, serial: u32, surface: ProxyId, offer_id: ProxyId) {
+        let &(ref offer, ref offered) = match self.offers.iter().find(|o| o.0.id() == offer_id) {
+            Some(offer) => offer,
+            None => return
+        };
+
+        if !offered.iter().any(|m| m == uri_list::MIME_TYPE) {
+            offer.accept(serial, None);
+            return;
+        }
+
+        // the offer only has actions since version 3 of the protocol, which wayland-client
+        // doesn't provide: accepting the MIME type is all the compositor needs
+        offer.accept(serial, Some(uri_list::MIME_TYPE.to_owned()));
+
+        let pipe = match receive(offer, uri_list::MIME_TYPE.to_owned()) {
+            Some(pipe) => pipe,
+            None => return
+        };
+        unsafe {
+            use std::os::unix::io::AsRawFd;
+            let fd = pipe.as_raw_fd();
+            let flags = libc::fcntl(fd, libc::F_GETFL);
+            libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK);
+        }
+
+        self.drag = Some(Drag {
+            offer: offer_id,
+            surface: surface,
+            pipe: Some(pipe),
+            data: Vec::new(),
+            paths: None,
+            dropped: false,
+        });
+    }
+
+    /// Delivers the dropped files once they have been dropped and read.
+    fn finish_drag(&mut self, out: &mut Vec<(GlutinEvent, ProxyId)>) {
+        let ready = self.drag.as_ref().map_or(false, |drag| drag.dropped && drag.paths.is_some());
+        if !ready {
+            return;
+        }
+
+        let drag = self.drag.take().unwrap();
+        let surface = drag.surface;
+        out.extend(drag.paths.unwrap().into_iter().map(|path| (GlutinEvent::DroppedFile(path), surface)));
+        self.destroy_unused_offers();
+    }
+
+    /// Destroys the offers that are neither the selection nor being dragged.
+    fn destroy_unused_offers(&mut self) {
+        let selection = self.selection;
+        let dragged = self.drag.as_ref().map(|drag| drag.offer);
+
+        let offers = mem::replace(&mut self.offers, Vec::new());
+        for (offer, mime_types) in offers {
+            let id = Some(offer.id());
+            if id == selection || id == dragged {
+                self.offers.push((offer, mime_types));
+            } else {
+                offer.destroy();
+            }
+        }
+    }
+}
+
+/// Asks the source of an offer to write its data in a pipe, and returns the read end of it.
+fn receive(offer: &WlDataOffer, mime_type: String) -> Option<File> {
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    offer.receive(mime_type, fds[1]);
+    unsafe { libc::close(fds[1]) };
+
+    Some(unsafe { File::from_raw_fd(fds[0]) })
+}
+
+/// Returns the MIME types that correspond to a MIME type of the public API, by order of
+/// preference.
+pub fn mime_types_for(mime_type: &str) -> Vec<String> {
+    if mime_type == TEXT_MIME_TYPE {
+        vec![mime_type.to_owned(), X11_TEXT_MIME_TYPE.to_owned()]
+    } else {
+        vec![mime_type.to_owned()]
+    }
+}
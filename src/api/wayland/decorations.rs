@@ -0,0 +1,314 @@
+//! Client-side decorations, since `wl_shell` compositors don't draw any.
+//!
+//! The borders are subsurfaces of the window, drawn around it in a shared memory buffer. They
+//! move and resize the window when clicked, and can be hidden while the window covers a
+//! monitor.
+
+use std::cmp;
+use std::io::{Seek, SeekFrom, Write};
+use std::mem;
+use std::os::unix::io::AsRawFd;
+
+use super::tempfile::TempFile;
+
+use wayland_client::{Event, EventIterator, Proxy, ProxyId};
+use wayland_client::wayland::compositor::{WlCompositor, WlSurface};
+use wayland_client::wayland::seat::{WlPointer, WlPointerButtonState, WlSeat};
+use wayland_client::wayland::shell::{WlShell, WlShellSurface, WlShellSurfaceResize};
+use wayland_client::wayland::shm::{WlBuffer, WlShm, WlShmFormat, WlShmPool};
+use wayland_client::wayland::subcompositor::{WlSubcompositor, WlSubsurface};
+
+// The borders are organised this way around the surface of the window:
+//
+//        0
+// ---|-------|---
+//    |       |
+//  3 | user  | 1
+//    |       |
+// ---|-------|---
+//        2
+const BORDER_TOP: usize = 0;
+const BORDER_RIGHT: usize = 1;
+const BORDER_BOTTOM: usize = 2;
+const BORDER_LEFT: usize = 3;
+
+const DECORATION_SIZE: i32 = 8;
+const DECORATION_TOP_SIZE: i32 = 24;
+
+/// Color of the borders, in ARGB.
+const DECORATION_COLOR: u32 = 0xFF444444;
+
+/// Linux event code of the left button.
+const BTN_LEFT: u32 = 0x110;
+
+/// Border the pointer is over.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Location {
+    None,
+    Top,
+    Right,
+    Bottom,
+    Left
+}
+
+/// Borders around the surface of a window, which is made a toplevel shell surface.
+pub struct DecoratedSurface {
+    shell_surface: WlShellSurface,
+    border_surfaces: Vec<(WlSurface, WlSubsurface)>,
+    buffers: Vec<WlBuffer>,
+    tempfile: TempFile,
+    pool: WlShmPool,
+    buffer_capacity: usize,
+    width: i32,
+    height: i32,
+    /// Whether the borders are drawn
+    decorate: bool,
+    _pointer: Option<WlPointer>,
+    location: Location,
+    coordinates: (f64, f64),
+    eventiter: EventIterator,
+    seat: Option<WlSeat>
+}
+
+impl DecoratedSurface {
+    /// Creates the borders around a surface of the given size, which excludes the borders.
+    pub fn new(surface: &WlSurface, width: i32, height: i32, compositor: &WlCompositor,
+               subcompositor: &WlSubcompositor, shm: &WlShm, shell: &WlShell, seat: Option<WlSeat>)
+               -> Result<DecoratedSurface, ()>
+    {
+        let eventiter = EventIterator::new();
+
+        let capacity = buffer_size(width, height);
+        let tempfile = match TempFile::new() {
+            Ok(tempfile) => tempfile,
+            Err(_) => return Err(())
+        };
+        if tempfile.set_len(capacity as u64).is_err() {
+            return Err(());
+        }
+        let mut pool = shm.create_pool(tempfile.as_raw_fd(), capacity as i32);
+        pool.set_evt_iterator(&eventiter);
+
+        let border_surfaces: Vec<_> = (0..4).map(|_| {
+            let mut border = compositor.create_surface();
+            border.set_evt_iterator(&eventiter);
+            let mut subsurface = subcompositor.get_subsurface(&border, surface);
+            subsurface.set_evt_iterator(&eventiter);
+            subsurface.set_desync();
+            (border, subsurface)
+        }).collect();
+
+        let mut shell_surface = shell.get_shell_surface(surface);
+        shell_surface.set_evt_iterator(&eventiter);
+        shell_surface.set_toplevel();
+
+        // the pointer events of the borders are used to move and resize the window
+        let pointer = seat.as_ref().map(|seat| {
+            let mut pointer = seat.get_pointer();
+            pointer.set_evt_iterator(&eventiter);
+            pointer
+        });
+
+        let mut decorated = DecoratedSurface {
+            shell_surface: shell_surface,
+            border_surfaces: border_surfaces,
+            buffers: Vec::new(),
+            tempfile: tempfile,
+            pool: pool,
+            buffer_capacity: capacity,
+            width: width,
+            height: height,
+            decorate: true,
+            _pointer: pointer,
+            location: Location::None,
+            coordinates: (0.0, 0.0),
+            eventiter: eventiter,
+            seat: seat
+        };
+        decorated.resize(width, height);
+        Ok(decorated)
+    }
+
+    /// Returns the shell surface of the window.
+    #[inline]
+    pub fn get_shell(&self) -> &WlShellSurface {
+        &self.shell_surface
+    }
+
+    /// Returns true if the borders are drawn.
+    #[inline]
+    pub fn is_decorated(&self) -> bool {
+        self.decorate
+    }
+
+    /// Shows or hides the borders, which are hidden while the window covers a monitor.
+    pub fn set_decorate(&mut self, decorate: bool) {
+        if self.decorate == decorate {
+            return;
+        }
+        self.decorate = decorate;
+        let (width, height) = (self.width, self.height);
+        self.resize(width, height);
+    }
+
+    /// Resizes the borders around a surface of the given size.
+    pub fn resize(&mut self, width: i32, height: i32) {
+        self.width = width;
+        self.height = height;
+        self.buffers.clear();
+
+        if !self.decorate {
+            for &(ref border, _) in &self.border_surfaces {
+                border.attach(None, 0, 0);
+                border.commit();
+            }
+            return;
+        }
+
+        let capacity = buffer_size(width, height);
+        if capacity > self.buffer_capacity {
+            if self.tempfile.set_len(capacity as u64).is_err() {
+                return;
+            }
+            self.pool.resize(capacity as i32);
+            self.buffer_capacity = capacity;
+        }
+
+        // every border uses the beginning of the buffer, which is filled with the same color
+        let pixel: [u8; 4] = unsafe { mem::transmute(DECORATION_COLOR) };
+        let data: Vec<u8> = pixel.iter().cloned().cycle().take(capacity).collect();
+        if self.tempfile.seek(SeekFrom::Start(0)).is_err() || self.tempfile.write_all(&data).is_err() {
+            return;
+        }
+        let _ = self.tempfile.flush();
+
+        let wide = width + DECORATION_SIZE * 2;
+        let borders = [
+            (BORDER_TOP, wide, DECORATION_TOP_SIZE, -DECORATION_SIZE, -DECORATION_TOP_SIZE),
+            (BORDER_RIGHT, DECORATION_SIZE, height, width, 0),
+            (BORDER_BOTTOM, wide, DECORATION_SIZE, -DECORATION_SIZE, height),
+            (BORDER_LEFT, DECORATION_SIZE, height, -DECORATION_SIZE, 0),
+        ];
+        for &(index, w, h, x, y) in &borders {
+            let buffer = self.pool.create_buffer(0, w, h, w * 4, WlShmFormat::Argb8888 as u32);
+            let (ref border, ref subsurface) = self.border_surfaces[index];
+            border.attach(Some(&buffer), 0, 0);
+            subsurface.set_position(x, y);
+            border.commit();
+            self.buffers.push(buffer);
+        }
+    }
+
+    /// Remembers which border the pointer entered, if it is one of ours.
+    fn pointer_entered(&mut self, surface: ProxyId) {
+        self.location = match self.border_surfaces.iter().position(|b| b.0.id() == surface) {
+            Some(BORDER_TOP) => Location::Top,
+            Some(BORDER_RIGHT) => Location::Right,
+            Some(BORDER_BOTTOM) => Location::Bottom,
+            Some(BORDER_LEFT) => Location::Left,
+            _ => Location::None
+        };
+    }
+
+    /// Returns how a click on the border under the pointer acts on the window: resizing it
+    /// from an edge, or moving it when clicking in the title bar.
+    fn action(&self) -> Option<WlShellSurfaceResize::WlShellSurfaceResize> {
+        let (x, y) = self.coordinates;
+        let left = x < DECORATION_SIZE as f64;
+        let right = x > (self.width + DECORATION_SIZE) as f64;
+
+        match self.location {
+            Location::Top if y < DECORATION_SIZE as f64 => Some(if left {
+                WlShellSurfaceResize::TopLeft
+            } else if right {
+                WlShellSurfaceResize::TopRight
+            } else {
+                WlShellSurfaceResize::Top
+            }),
+            Location::Top if left => Some(WlShellSurfaceResize::Left),
+            Location::Top if right => Some(WlShellSurfaceResize::Right),
+            Location::Top => None,
+            Location::Bottom => Some(if left {
+                WlShellSurfaceResize::BottomLeft
+            } else if right {
+                WlShellSurfaceResize::BottomRight
+            } else {
+                WlShellSurfaceResize::Bottom
+            }),
+            Location::Left => Some(WlShellSurfaceResize::Left),
+            Location::Right => Some(WlShellSurfaceResize::Right),
+            Location::None => Some(WlShellSurfaceResize::None)
+        }
+    }
+}
+
+/// Returns the configure events of the shell surface, with the size suggested for the window
+/// and its borders, after handling the clicks on the borders.
+impl Iterator for DecoratedSurface {
+    type Item = (WlShellSurfaceResize::WlShellSurfaceResize, i32, i32);
+
+    fn next(&mut self) -> Option<(WlShellSurfaceResize::WlShellSurfaceResize, i32, i32)> {
+        use wayland_client::wayland::WaylandProtocolEvent;
+        use wayland_client::wayland::seat::WlPointerEvent;
+        use wayland_client::wayland::shell::WlShellSurfaceEvent;
+
+        loop {
+            let event = match self.eventiter.next() {
+                Some(Event::Wayland(event)) => event,
+                None => return None
+            };
+
+            match event {
+                WaylandProtocolEvent::WlPointer(_, WlPointerEvent::Enter(_, surface, x, y)) => {
+                    self.pointer_entered(surface);
+                    self.coordinates = (x, y);
+                },
+                WaylandProtocolEvent::WlPointer(_, WlPointerEvent::Leave(..)) => {
+                    self.location = Location::None;
+                },
+                WaylandProtocolEvent::WlPointer(_, WlPointerEvent::Motion(_, x, y)) => {
+                    self.coordinates = (x, y);
+                },
+                WaylandProtocolEvent::WlPointer(_, WlPointerEvent::Button(serial, _, button, state)) => {
+                    // the pointer is only over a border when it is shown
+                    if button != BTN_LEFT || self.location == Location::None || !self.decorate {
+                        continue;
+                    }
+                    if let WlPointerButtonState::Released = state {
+                        continue;
+                    }
+                    if let Some(ref seat) = self.seat {
+                        match self.action() {
+                            Some(edges) => self.shell_surface.resize(seat, serial, edges),
+                            None => self.shell_surface.move_(seat, serial)
+                        }
+                    }
+                },
+                WaylandProtocolEvent::WlShellSurface(_, WlShellSurfaceEvent::Ping(serial)) => {
+                    self.shell_surface.pong(serial);
+                },
+                WaylandProtocolEvent::WlShellSurface(_, WlShellSurfaceEvent::Configure(edges, w, h)) => {
+                    return Some((edges, w, h));
+                },
+                _ => ()
+            }
+        }
+    }
+}
+
+/// Returns the size in bytes of a buffer that fits any of the borders.
+fn buffer_size(width: i32, height: i32) -> usize {
+    let pixels = cmp::max(DECORATION_TOP_SIZE * (width + DECORATION_SIZE * 2),
+                          DECORATION_SIZE * height);
+    cmp::max(pixels, DECORATION_TOP_SIZE * DECORATION_SIZE) as usize * 4
+}
+
+/// Substracts the size of the borders from the size of a decorated window.
+pub fn substract_borders(width: i32, height: i32) -> (i32, i32) {
+    (width - 2 * DECORATION_SIZE, height - DECORATION_SIZE - DECORATION_TOP_SIZE)
+}
+
+/// Adds the size of the borders to the size of the surface of a decorated window.
+pub fn add_borders(width: i32, height: i32) -> (i32, i32) {
+    (width + 2 * DECORATION_SIZE, height + DECORATION_SIZE + DECORATION_TOP_SIZE)
+}
@@ -1,5 +1,6 @@
 use std::collections::HashSet;
 
+use Touch;
 use TouchPhase;
 use Event as GlutinEvent;
 use ElementState;
@@ -10,7 +11,7 @@ use wayland_client::Event as WaylandEvent;
 use wayland_client::ProxyId;
 use wayland_client::wayland::WaylandProtocolEvent as WPE;
 use wayland_client::wayland::seat::{WlSeat, WlSeatEvent, WlPointerEvent,
-                                    WlPointerButtonState,
+                                    WlPointerButtonState, WlTouchEvent,
                                     WlPointerAxis, WlSeatCapability};
 
 use super::wayland_kbd::MappedKeyboard;
@@ -22,9 +23,22 @@ pub fn translate_event(
     focuses: &mut WaylandFocuses,
     known_surfaces: &HashSet<ProxyId>,
     seat: Option<&WlSeat>,
-    ) -> Option<(GlutinEvent, ProxyId)>
+    ) -> Vec<(GlutinEvent, ProxyId)>
 {
     let WaylandEvent::Wayland(wayland_evt) = evt;
+    match wayland_evt {
+        WPE::WlTouch(_, touch_evt) => translate_touch_event(touch_evt, focuses, known_surfaces),
+        other => translate_seat_event(other, focuses, known_surfaces, seat).into_iter().collect()
+    }
+}
+
+fn translate_seat_event(
+    wayland_evt: WPE,
+    focuses: &mut WaylandFocuses,
+    known_surfaces: &HashSet<ProxyId>,
+    seat: Option<&WlSeat>,
+    ) -> Option<(GlutinEvent, ProxyId)>
+{
     match wayland_evt {
         WPE::WlSeat(_, seat_evt) => match seat_evt {
             WlSeatEvent::Capabilities(cap) => {
@@ -33,6 +47,11 @@ pub fn translate_event(
                         focuses.pointer = Some(seat.get_pointer());
                     }
                 }
+                if cap.contains(WlSeatCapability::Touch) && focuses.touch.is_none() {
+                    if let Some(seat) = seat {
+                        focuses.touch = Some(seat.get_touch());
+                    }
+                }
                 if cap.contains(WlSeatCapability::Keyboard) && focuses.keyboard.is_none() {
                     if let Some(seat) = seat {
                         match MappedKeyboard::new(seat) {
@@ -48,7 +67,8 @@ pub fn translate_event(
             _ => None
         },
         WPE::WlPointer(_, pointer_evt) => match pointer_evt {
-            WlPointerEvent::Enter(_, surface, x, y) => {
+            WlPointerEvent::Enter(serial, surface, x, y) => {
+                focuses.serial = serial;
                 if known_surfaces.contains(&surface) {
                     focuses.pointer_on = Some(surface);
                     focuses.pointer_at = Some((x, y));
@@ -70,7 +90,11 @@ pub fn translate_event(
                     None
                 }
             }
-            WlPointerEvent::Button(_, _, button, state) => {
+            WlPointerEvent::Button(serial, _, button, state) => {
+                focuses.serial = serial;
+                if let WlPointerButtonState::Pressed = state {
+                    focuses.button_serial = serial;
+                }
                 if let Some(surface) = focuses.pointer_on {
                     Some((GlutinEvent::MouseInput(
                         match state {
@@ -110,3 +134,60 @@ pub fn translate_event(
         _ => None
     }
 }
+
+fn translate_touch_event(
+    touch_evt: WlTouchEvent,
+    focuses: &mut WaylandFocuses,
+    known_surfaces: &HashSet<ProxyId>,
+    ) -> Vec<(GlutinEvent, ProxyId)>
+{
+    let touch = |phase, location, id: i32, surface| {
+        (GlutinEvent::Touch(Touch {
+            phase: phase,
+            location: location,
+            id: id as u64,
+            // there is no pressure in the protocol, and the shape and orientation
+            // events are more recent than the wl_touch version we bind, see `Touch`
+            force: None,
+            contact: None,
+            device_id: None
+        }), surface)
+    };
+
+    match touch_evt {
+        WlTouchEvent::Down(_, _, surface, id, x, y) => {
+            if known_surfaces.contains(&surface) {
+                focuses.touch_points.push((id, surface, (x, y)));
+                vec![touch(TouchPhase::Started, (x, y), id, surface)]
+            } else {
+                Vec::new()
+            }
+        }
+        WlTouchEvent::Motion(_, id, x, y) => {
+            match focuses.touch_points.iter_mut().find(|p| p.0 == id) {
+                Some(point) => {
+                    point.2 = (x, y);
+                    vec![touch(TouchPhase::Moved, (x, y), id, point.1)]
+                }
+                None => Vec::new()
+            }
+        }
+        WlTouchEvent::Up(_, _, id) => {
+            // the up event has no position, use the last known one
+            match focuses.touch_points.iter().position(|p| p.0 == id) {
+                Some(idx) => {
+                    let (_, surface, location) = focuses.touch_points.remove(idx);
+                    vec![touch(TouchPhase::Ended, location, id, surface)]
+                }
+                None => Vec::new()
+            }
+        }
+        WlTouchEvent::Cancel => {
+            // the compositor took over the touch sequence, e.g. for a gesture
+            focuses.touch_points.drain(..).map(|(id, surface, location)| {
+                touch(TouchPhase::Cancelled, location, id, surface)
+            }).collect()
+        }
+        _ => Vec::new()
+    }
+}
@@ -0,0 +1,180 @@
+//! Touchpad gestures, through the pointer gestures protocol.
+//!
+//! The compositor recognizes the gestures and sends them to the surface under the pointer, from
+//! their beginning to their end.
+
+use std::mem;
+use std::sync::Mutex;
+
+use libc::c_void;
+
+use wayland_sys::client::wl_proxy;
+use wayland_sys::common::{wl_argument, wl_message};
+
+use events::{Gesture, GestureKind, TouchPhase};
+use Event as GlutinEvent;
+
+use super::protocols::{self, Registry};
+use super::protocols::pointer_gestures_v1::{ZWP_POINTER_GESTURES_V1_INTERFACE,
+                                            ZWP_POINTER_GESTURE_PINCH_V1_INTERFACE,
+                                            ZWP_POINTER_GESTURE_SWIPE_V1_INTERFACE};
+use super::protocols::pointer_gestures_v1::zwp_pointer_gestures_v1 as manager;
+use super::protocols::pointer_gestures_v1::zwp_pointer_gesture_pinch_v1 as pinch;
+use super::protocols::pointer_gestures_v1::zwp_pointer_gesture_swipe_v1 as swipe;
+
+pub struct Gestures {
+    manager: *mut wl_proxy,
+    state: Box<Mutex<GestureState>>
+}
+
+// the objects are only used while the state is locked, or when dispatching events
+unsafe impl Send for Gestures {}
+unsafe impl Sync for Gestures {}
+
+struct GestureState {
+    /// Pointer whose gestures are received
+    pointer: *mut wl_proxy,
+    /// Surface and number of fingers of the gesture in progress
+    current: Option<(*mut wl_proxy, u32)>,
+    /// Scale of the pinch in progress, the compositor sends it since the beginning of the pinch
+    pinch_scale: f64,
+    /// Events of the gestures, along with the surface they happened over
+    events: Vec<(GlutinEvent, *mut wl_proxy)>
+}
+
+impl Gestures {
+    /// Binds the gestures global, if the compositor supports the protocol.
+    pub fn new(registry: &Registry) -> Option<Gestures> {
+        unsafe { registry.bind(&ZWP_POINTER_GESTURES_V1_INTERFACE) }.map(|manager| {
+            Gestures {
+                manager: manager,
+                state: Box::new(Mutex::new(GestureState {
+                    pointer: 0 as *mut wl_proxy,
+                    current: None,
+                    pinch_scale: 1.0,
+                    events: Vec::new()
+                }))
+            }
+        })
+    }
+
+    /// Receives the gestures of the pointer of the seat, once it exists.
+    pub fn set_pointer(&self, pointer: *mut wl_proxy) {
+        let mut state = self.state.lock().unwrap();
+        if !state.pointer.is_null() {
+            return;
+        }
+        state.pointer = pointer;
+        let data = &*self.state as *const _ as *mut c_void;
+        unsafe {
+            let swipe = protocols::create_for(self.manager, manager::request::GET_SWIPE_GESTURE,
+                                              &ZWP_POINTER_GESTURE_SWIPE_V1_INTERFACE, pointer);
+            if !swipe.is_null() {
+                protocols::set_dispatcher(swipe, swipe_dispatcher, data);
+            }
+            let pinch = protocols::create_for(self.manager, manager::request::GET_PINCH_GESTURE,
+                                              &ZWP_POINTER_GESTURE_PINCH_V1_INTERFACE, pointer);
+            if !pinch.is_null() {
+                protocols::set_dispatcher(pinch, pinch_dispatcher, data);
+            }
+        }
+    }
+
+    /// Returns the events generated while dispatching, with the surface they are for.
+    pub fn take_events(&self) -> Vec<(GlutinEvent, *mut wl_proxy)> {
+        mem::replace(&mut self.state.lock().unwrap().events, Vec::new())
+    }
+}
+
+impl GestureState {
+    /// Handles the beginning and the end of a gesture, which are the same for both kinds.
+    ///
+    /// Returns the surface, the number of fingers and the phase of the event.
+    unsafe fn phase(&mut self, opcode: u32, args: *const wl_argument)
+                    -> Option<(*mut wl_proxy, u32, TouchPhase)>
+    {
+        // the opcodes are the same for swipes and pinches
+        match opcode {
+            swipe::event::BEGIN => {
+                let current = (protocols::arg_object(args, 2), protocols::arg_uint(args, 3));
+                self.current = Some(current);
+                self.pinch_scale = 1.0;
+                Some((current.0, current.1, TouchPhase::Started))
+            },
+            swipe::event::UPDATE => {
+                self.current.map(|(surface, fingers)| (surface, fingers, TouchPhase::Moved))
+            },
+            swipe::event::END => {
+                let phase = if protocols::arg_int(args, 2) != 0 {
+                    TouchPhase::Cancelled
+                } else {
+                    TouchPhase::Ended
+                };
+                self.current.take().map(|(surface, fingers)| (surface, fingers, phase))
+            },
+            _ => None
+        }
+    }
+
+    unsafe fn swipe_event(&mut self, opcode: u32, args: *const wl_argument) {
+        let (surface, fingers, phase) = match self.phase(opcode, args) {
+            Some(phase) => phase,
+            None => return
+        };
+        let translation = if opcode == swipe::event::UPDATE {
+            (protocols::arg_fixed(args, 1), protocols::arg_fixed(args, 2))
+        } else {
+            (0.0, 0.0)
+        };
+        self.events.push((GlutinEvent::Gesture(Gesture {
+            kind: GestureKind::Swipe,
+            phase: phase,
+            fingers: fingers,
+            scale_delta: 1.0,
+            rotation_delta: 0.0,
+            translation_delta: translation
+        }), surface));
+    }
+
+    unsafe fn pinch_event(&mut self, opcode: u32, args: *const wl_argument) {
+        let (surface, fingers, phase) = match self.phase(opcode, args) {
+            Some(phase) => phase,
+            None => return
+        };
+        let (translation, scale_delta, rotation) = if opcode == pinch::event::UPDATE {
+            let scale = protocols::arg_fixed(args, 3);
+            let scale_delta = if self.pinch_scale > 0.0 { scale / self.pinch_scale } else { 1.0 };
+            self.pinch_scale = scale;
+            ((protocols::arg_fixed(args, 1), protocols::arg_fixed(args, 2)), scale_delta,
+             protocols::arg_fixed(args, 4))
+        } else {
+            ((0.0, 0.0), 1.0, 0.0)
+        };
+        self.events.push((GlutinEvent::Gesture(Gesture {
+            kind: GestureKind::Pinch,
+            phase: phase,
+            fingers: fingers,
+            scale_delta: scale_delta,
+            rotation_delta: rotation,
+            translation_delta: translation
+        }), surface));
+    }
+}
+
+extern "C" fn swipe_dispatcher(_: *const c_void, proxy: *mut c_void, opcode: u32,
+                               _: *const wl_message, args: *const wl_argument)
+{
+    unsafe {
+        let state = &*(protocols::user_data(proxy) as *const Mutex<GestureState>);
+        state.lock().unwrap().swipe_event(opcode, args);
+    }
+}
+
+extern "C" fn pinch_dispatcher(_: *const c_void, proxy: *mut c_void, opcode: u32,
+                               _: *const wl_message, args: *const wl_argument)
+{
+    unsafe {
+        let state = &*(protocols::user_data(proxy) as *const Mutex<GestureState>);
+        state.lock().unwrap().pinch_event(opcode, args);
+    }
+}
@@ -20,6 +20,8 @@ pub fn translate_kbd_events(
         for evt in mkbd {
             match evt {
                 MappedKeyboardEvent::KeyEvent(kevt) => {
+                    // copying is usually triggered by a key press
+                    focuses.serial = kevt.serial;
                     if let Some(surface) = focuses.keyboard_on {
                         let vkcode = match kevt.keycode {
                              1 => Some(VirtualKeyCode::Escape),
@@ -57,7 +59,8 @@ pub fn translate_kbd_events(
                     
                 }
                 MappedKeyboardEvent::Other(oevt) => match oevt {
-                    WlKeyboardEvent::Enter(_, surface, _) => {
+                    WlKeyboardEvent::Enter(serial, surface, _) => {
+                        focuses.serial = serial;
                         if known_surfaces.contains(&surface) {
                             focuses.keyboard_on = Some(surface);
                             out.push((GlutinEvent::Focused(true), surface));
@@ -3,13 +3,20 @@
 pub use self::monitor::{MonitorId, get_available_monitors, get_primary_monitor};
 pub use self::window::{PollEventsIterator, WaitEventsIterator, Window, WindowProxy};
 
+extern crate tempfile;
 extern crate wayland_kbd;
-extern crate wayland_window;
 
 mod context;
+mod data_device;
+mod decorations;
 mod events;
+mod gestures;
 mod keyboard;
 mod monitor;
+mod primary_selection;
+mod protocols;
+mod tablet;
+mod text_input;
 mod window;
 
 #[inline]
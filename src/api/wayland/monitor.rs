@@ -3,6 +3,8 @@ use std::collections::VecDeque;
 use wayland_client::{ProxyId, EventIterator};
 use wayland_client::wayland::output::WlOutput;
 
+use VideoMode;
+
 use super::context::WAYLAND_CONTEXT;
 
 #[derive(Clone)]
@@ -34,13 +36,17 @@ impl MonitorId {
     pub fn get_dimensions(&self) -> (u32, u32) {
         WAYLAND_CONTEXT.as_ref().and_then(|ctxt| ctxt.monitor_dimensions(self.0)).unwrap()
     }
+
+    pub fn get_video_modes(&self) -> Vec<VideoMode> {
+        WAYLAND_CONTEXT.as_ref().map(|ctxt| ctxt.monitor_video_modes(self.0)).unwrap_or_else(Vec::new)
+    }
 }
 
 pub fn proxid_from_monitorid(x: &MonitorId) -> ProxyId {
     x.0
 }
 
-pub fn init_monitors(outputs: &mut Vec<(WlOutput, u32, u32, String)>, evts: EventIterator) {
+pub fn init_monitors(outputs: &mut Vec<(WlOutput, u32, u32, String, Vec<VideoMode>)>, evts: EventIterator) {
     use wayland_client::{Event, Proxy};
     use wayland_client::wayland::WaylandProtocolEvent;
     use wayland_client::wayland::output::{WlOutputEvent, WlOutputMode};
@@ -56,14 +62,19 @@ pub fn init_monitors(outputs: &mut Vec<(WlOutput, u32, u32, String)>, evts: Even
                         }
                     }
                 },
-                WlOutputEvent::Mode(flags, width, height, _) => {
-                    if flags.contains(WlOutputMode::Current) {
-                        for o in outputs.iter_mut() {
-                            if o.0.id() == pid {
+                WlOutputEvent::Mode(flags, width, height, refresh) => {
+                    for o in outputs.iter_mut() {
+                        if o.0.id() == pid {
+                            if flags.contains(WlOutputMode::Current) {
                                 o.1 = width as u32;
                                 o.2 = height as u32;
-                                break
                             }
+                            // the refresh rate is in mHz
+                            o.4.push(VideoMode {
+                                dimensions: (width as u32, height as u32),
+                                refresh_rate: ((refresh + 500) / 1000) as u32,
+                            });
+                            break
                         }
                     }
                 },
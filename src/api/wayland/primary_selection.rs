@@ -0,0 +1,260 @@
+//! The primary selection, through the primary selection protocol.
+//!
+//! It works like the clipboard of `wl_data_device`: the compositor announces the offer that
+//! holds the selection, whose contents are read from a pipe, and we provide our own selection
+//! through a source that writes its contents in the pipes of the other clients.
+
+use std::ffi::CString;
+use std::fs::File;
+use std::io::Write;
+use std::mem;
+use std::os::unix::io::FromRawFd;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use libc::{self, c_void};
+
+use wayland_sys::client::*;
+use wayland_sys::common::{wl_argument, wl_message};
+
+use super::data_device::{self, ClipboardData};
+use super::protocols::{self, Registry};
+use super::protocols::primary_selection_v1::{ZWP_PRIMARY_SELECTION_DEVICE_MANAGER_V1_INTERFACE,
+                                             ZWP_PRIMARY_SELECTION_DEVICE_V1_INTERFACE,
+                                             ZWP_PRIMARY_SELECTION_SOURCE_V1_INTERFACE};
+use super::protocols::primary_selection_v1::zwp_primary_selection_device_manager_v1 as manager;
+use super::protocols::primary_selection_v1::zwp_primary_selection_device_v1 as device;
+use super::protocols::primary_selection_v1::zwp_primary_selection_offer_v1 as offer;
+use super::protocols::primary_selection_v1::zwp_primary_selection_source_v1 as source;
+
+pub struct PrimarySelection {
+    manager: *mut wl_proxy,
+    state: Box<Mutex<SelectionState>>
+}
+
+// the objects are only used while the state is locked, or when dispatching events
+unsafe impl Send for PrimarySelection {}
+unsafe impl Sync for PrimarySelection {}
+
+struct SelectionState {
+    device: *mut wl_proxy,
+    /// Offers announced by the compositor, with the MIME types they provide
+    offers: Vec<(*mut wl_proxy, Vec<String>)>,
+    /// Offer that currently holds the primary selection
+    selection: Option<*mut wl_proxy>,
+    /// Our source if we own the primary selection, with the data for every MIME type
+    source: Option<(*mut wl_proxy, Vec<(String, Arc<Vec<u8>>)>)>
+}
+
+impl PrimarySelection {
+    /// Gets the primary selection of a seat, if the compositor supports the protocol.
+    pub fn new(registry: &Registry, wl_seat: *mut wl_proxy) -> Option<PrimarySelection> {
+        unsafe {
+            let manager = match registry.bind(&ZWP_PRIMARY_SELECTION_DEVICE_MANAGER_V1_INTERFACE) {
+                Some(manager) => manager,
+                None => return None
+            };
+            let device = protocols::create_for(manager, manager::request::GET_DEVICE,
+                                               &ZWP_PRIMARY_SELECTION_DEVICE_V1_INTERFACE, wl_seat);
+            if device.is_null() {
+                protocols::destroy(manager, manager::request::DESTROY);
+                return None;
+            }
+
+            let state = Box::new(Mutex::new(SelectionState {
+                device: device,
+                offers: Vec::new(),
+                selection: None,
+                source: None
+            }));
+            protocols::set_dispatcher(device, device_dispatcher, &*state as *const _ as *mut c_void);
+
+            Some(PrimarySelection {
+                manager: manager,
+                state: state
+            })
+        }
+    }
+
+    /// Returns the contents of the primary selection in the given MIME type.
+    pub fn get(&self, mime_type: &str) -> Option<ClipboardData> {
+        let state = self.state.lock().unwrap();
+        let mime_types = data_device::mime_types_for(mime_type);
+
+        if let Some((_, ref contents)) = state.source {
+            return mime_types.iter()
+                             .filter_map(|mime_type| contents.iter().find(|c| c.0 == *mime_type))
+                             .map(|c| ClipboardData::Local((*c.1).clone()))
+                             .next();
+        }
+
+        let &(offer, ref offered) = match state.offers.iter().find(|o| Some(o.0) == state.selection) {
+            Some(offer) => offer,
+            None => return None
+        };
+        let mime_type = match mime_types.into_iter().find(|m| offered.contains(m)) {
+            Some(mime_type) => mime_type,
+            None => return None
+        };
+
+        unsafe { receive(offer, mime_type) }.map(ClipboardData::Remote)
+    }
+
+    /// Replaces the contents of the primary selection. `serial` must be the serial of a recent
+    /// input event, otherwise the compositor ignores the request.
+    pub fn set(&self, serial: u32, mime_type: &str, data: Vec<u8>) {
+        let mut state = self.state.lock().unwrap();
+
+        let source = unsafe {
+            protocols::create(self.manager, manager::request::CREATE_SOURCE,
+                              &ZWP_PRIMARY_SELECTION_SOURCE_V1_INTERFACE)
+        };
+        if source.is_null() {
+            return;
+        }
+
+        let data = Arc::new(data);
+        let contents: Vec<_> = data_device::mime_types_for(mime_type).into_iter()
+                                                                     .map(|mime_type| (mime_type, data.clone()))
+                                                                     .collect();
+        unsafe {
+            protocols::set_dispatcher(source, source_dispatcher, &*self.state as *const _ as *mut c_void);
+            for &(ref mime_type, _) in &contents {
+                let mime_type = CString::new(mime_type.as_bytes()).unwrap();
+                ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_marshal, source,
+                              source::request::OFFER, mime_type.as_ptr());
+            }
+            ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_marshal, state.device,
+                          device::request::SET_SELECTION, source, serial);
+        }
+
+        if let Some((old_source, _)) = state.source.take() {
+            unsafe { protocols::destroy(old_source, source::request::DESTROY) };
+        }
+        state.source = Some((source, contents));
+    }
+}
+
+impl Drop for PrimarySelection {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap();
+        unsafe {
+            if let Some((source, _)) = state.source.take() {
+                protocols::destroy(source, source::request::DESTROY);
+            }
+            for (offer, _) in mem::replace(&mut state.offers, Vec::new()) {
+                protocols::destroy(offer, offer::request::DESTROY);
+            }
+            protocols::destroy(state.device, device::request::DESTROY);
+            protocols::destroy(self.manager, manager::request::DESTROY);
+        }
+    }
+}
+
+impl SelectionState {
+    /// Destroys the offers that don't hold the selection anymore.
+    fn destroy_unused_offers(&mut self) {
+        let selection = self.selection;
+        let offers = mem::replace(&mut self.offers, Vec::new());
+        for (offer, mime_types) in offers {
+            if Some(offer) == selection {
+                self.offers.push((offer, mime_types));
+            } else {
+                unsafe { protocols::destroy(offer, offer::request::DESTROY) };
+            }
+        }
+    }
+}
+
+/// Asks the source of an offer to write its data in a pipe, and returns the read end of it.
+unsafe fn receive(offer: *mut wl_proxy, mime_type: String) -> Option<File> {
+    let mime_type = match CString::new(mime_type) {
+        Ok(mime_type) => mime_type,
+        Err(_) => return None
+    };
+    let mut fds = [0; 2];
+    if libc::pipe(fds.as_mut_ptr()) != 0 {
+        return None;
+    }
+    ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_marshal, offer, offer::request::RECEIVE,
+                  mime_type.as_ptr(), fds[1]);
+    libc::close(fds[1]);
+
+    Some(File::from_raw_fd(fds[0]))
+}
+
+unsafe fn state<'a>(proxy: *mut c_void) -> &'a Mutex<SelectionState> {
+    &*(protocols::user_data(proxy) as *const Mutex<SelectionState>)
+}
+
+extern "C" fn device_dispatcher(_: *const c_void, proxy: *mut c_void, opcode: u32,
+                                _: *const wl_message, args: *const wl_argument)
+{
+    unsafe {
+        let mut state = state(proxy).lock().unwrap();
+        match opcode {
+            device::event::DATA_OFFER => {
+                let offer = protocols::arg_object(args, 0);
+                protocols::set_dispatcher(offer, offer_dispatcher, protocols::user_data(proxy));
+                state.offers.push((offer, Vec::new()));
+            },
+            device::event::SELECTION => {
+                let offer = protocols::arg_object(args, 0);
+                state.selection = if offer.is_null() { None } else { Some(offer) };
+                state.destroy_unused_offers();
+            },
+            _ => ()
+        }
+    }
+}
+
+extern "C" fn offer_dispatcher(_: *const c_void, proxy: *mut c_void, opcode: u32,
+                               _: *const wl_message, args: *const wl_argument)
+{
+    unsafe {
+        let mut state = state(proxy).lock().unwrap();
+        if opcode == offer::event::OFFER {
+            let mime_type = protocols::arg_string(args, 0);
+            let offer = proxy as *mut wl_proxy;
+            if let (Some(&mut (_, ref mut mime_types)), Some(mime_type)) =
+                (state.offers.iter_mut().find(|o| o.0 == offer), mime_type)
+            {
+                mime_types.push(mime_type);
+            }
+        }
+    }
+}
+
+extern "C" fn source_dispatcher(_: *const c_void, proxy: *mut c_void, opcode: u32,
+                                _: *const wl_message, args: *const wl_argument)
+{
+    unsafe {
+        let mut state = state(proxy).lock().unwrap();
+        let source = proxy as *mut wl_proxy;
+        match opcode {
+            source::event::SEND => {
+                let mime_type = protocols::arg_string(args, 0);
+                let mut file = File::from_raw_fd(protocols::arg_fd(args, 1));
+                let data = match state.source {
+                    Some((ours, ref contents)) if ours == source => {
+                        contents.iter().find(|c| Some(&c.0) == mime_type.as_ref()).map(|c| c.1.clone())
+                    },
+                    _ => None
+                };
+                if let Some(data) = data {
+                    // don't block the event loop while the other client reads
+                    thread::spawn(move || { let _ = file.write_all(&data); });
+                }
+            },
+            source::event::CANCELLED => {
+                // another client took over the primary selection, the sources we replaced are
+                // already destroyed
+                if state.source.as_ref().map(|s| s.0) == Some(source) {
+                    state.source = None;
+                    protocols::destroy(source, source::request::DESTROY);
+                }
+            },
+            _ => ()
+        }
+    }
+}
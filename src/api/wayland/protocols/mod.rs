@@ -0,0 +1,180 @@
+//! Protocol extensions that `wayland-client` doesn't provide.
+//!
+//! The interfaces are described for libwayland like `wayland-scanner` would, and their objects
+//! are handled directly through `wayland-sys`: requests are marshalled by opcode, and the events
+//! of each object go to a dispatcher function along with its user data.
+
+use std::ffi::CStr;
+use std::ptr;
+
+use libc::{c_int, c_void};
+
+use wayland_sys::client::*;
+use wayland_sys::common::{wl_argument, wl_dispatcher_func_t, wl_interface, wl_message};
+
+macro_rules! c_str {
+    ($s:expr) => (concat!($s, "\0").as_ptr() as *const ::libc::c_char)
+}
+
+pub mod pointer_gestures_v1;
+pub mod primary_selection_v1;
+pub mod tablet_v2;
+pub mod text_input_v3;
+
+/// Wraps the interface descriptions so that they can be statics, they are never modified.
+pub struct Static<T>(pub T);
+
+unsafe impl<T> Sync for Static<T> {}
+
+/// Function receiving the events of an object: its implementation pointer, the object, the
+/// opcode of the event, its description and its arguments.
+///
+/// libwayland ignores what the dispatchers of a client return, `wayland-sys` declares them
+/// without a return value.
+pub type Dispatcher = wl_dispatcher_func_t;
+
+const NULL: *const wl_interface = 0 as *const wl_interface;
+
+static WL_REGISTRY_TYPES: Static<[*const wl_interface; 4]> = Static([NULL, NULL, NULL, NULL]);
+
+static WL_REGISTRY_REQUESTS: Static<[wl_message; 1]> = Static([
+    wl_message { name: c_str!("bind"), signature: c_str!("usun"), types: &WL_REGISTRY_TYPES.0[0] },
+]);
+
+static WL_REGISTRY_EVENTS: Static<[wl_message; 2]> = Static([
+    wl_message { name: c_str!("global"), signature: c_str!("usu"), types: &WL_REGISTRY_TYPES.0[0] },
+    wl_message { name: c_str!("global_remove"), signature: c_str!("u"), types: &WL_REGISTRY_TYPES.0[0] },
+]);
+
+static WL_REGISTRY_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("wl_registry"),
+    version: 1,
+    request_count: 1,
+    requests: &WL_REGISTRY_REQUESTS.0 as *const wl_message,
+    event_count: 2,
+    events: &WL_REGISTRY_EVENTS.0 as *const wl_message,
+});
+
+/// Opcode of `wl_display.get_registry`.
+const WL_DISPLAY_GET_REGISTRY: u32 = 1;
+/// Opcode of `wl_registry.bind`.
+const WL_REGISTRY_BIND: u32 = 0;
+
+/// Binds globals of the registry that `wayland-client` doesn't know about.
+///
+/// The globals are the ones listed by `wayland-client`, they are bound through a registry of
+/// our own which only exists while binding.
+pub struct Registry {
+    registry: *mut wl_proxy,
+    globals: Vec<(u32, String, u32)>
+}
+
+impl Registry {
+    pub unsafe fn new(display: *mut wl_proxy, globals: &[(u32, String, u32)]) -> Registry {
+        let registry = ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_marshal_constructor,
+                                     display, WL_DISPLAY_GET_REGISTRY,
+                                     &WL_REGISTRY_INTERFACE.0 as *const wl_interface,
+                                     ptr::null_mut::<c_void>());
+        Registry {
+            registry: registry,
+            globals: globals.to_vec()
+        }
+    }
+
+    /// Binds the first global with the name of the interface, if the compositor has one.
+    ///
+    /// The object is created at version 1, which is the one the interfaces are described at.
+    pub unsafe fn bind(&self, interface: &'static Static<wl_interface>) -> Option<*mut wl_proxy> {
+        if self.registry.is_null() {
+            return None;
+        }
+        let name = CStr::from_ptr(interface.0.name).to_string_lossy().into_owned();
+        let global = match self.globals.iter().find(|&&(_, ref iface, _)| *iface == name) {
+            Some(&(global, _, _)) => global,
+            None => return None
+        };
+        let proxy = ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_marshal_constructor,
+                                  self.registry, WL_REGISTRY_BIND,
+                                  &interface.0 as *const wl_interface,
+                                  global, interface.0.name, 1u32, ptr::null_mut::<c_void>());
+        if proxy.is_null() { None } else { Some(proxy) }
+    }
+}
+
+impl Drop for Registry {
+    fn drop(&mut self) {
+        // the registry has no destructor, only our side of it goes away
+        if !self.registry.is_null() {
+            unsafe { ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_destroy, self.registry) };
+        }
+    }
+}
+
+/// Sends a request creating a new object, the new id being the first argument of the request.
+pub unsafe fn create(proxy: *mut wl_proxy, opcode: u32, interface: &'static Static<wl_interface>)
+                     -> *mut wl_proxy
+{
+    ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_marshal_constructor, proxy, opcode,
+                  &interface.0 as *const wl_interface, ptr::null_mut::<c_void>())
+}
+
+/// Same as `create`, for requests taking an object after the new id.
+pub unsafe fn create_for(proxy: *mut wl_proxy, opcode: u32, interface: &'static Static<wl_interface>,
+                         object: *mut wl_proxy) -> *mut wl_proxy
+{
+    ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_marshal_constructor, proxy, opcode,
+                  &interface.0 as *const wl_interface, ptr::null_mut::<c_void>(), object)
+}
+
+/// Sends the destructor request of an object, and destroys our side of it.
+pub unsafe fn destroy(proxy: *mut wl_proxy, opcode: u32) {
+    ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_marshal, proxy, opcode);
+    ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_destroy, proxy);
+}
+
+/// Sets the function receiving the events of an object, with the data it gets along.
+pub unsafe fn set_dispatcher(proxy: *mut wl_proxy, dispatcher: Dispatcher, data: *mut c_void) {
+    ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_add_dispatcher, proxy, dispatcher,
+                  ptr::null(), data);
+}
+
+/// Returns the data given to `set_dispatcher`.
+pub unsafe fn user_data(proxy: *mut c_void) -> *mut c_void {
+    ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_get_user_data, proxy as *mut wl_proxy)
+}
+
+/// Returns the protocol id of an object.
+pub unsafe fn id(proxy: *mut wl_proxy) -> u32 {
+    ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_get_id, proxy)
+}
+
+// All the arguments share the size of a pointer, and their values start at its beginning.
+
+pub unsafe fn arg_int(args: *const wl_argument, index: isize) -> i32 {
+    *(args.offset(index) as *const i32)
+}
+
+pub unsafe fn arg_uint(args: *const wl_argument, index: isize) -> u32 {
+    *(args.offset(index) as *const u32)
+}
+
+pub unsafe fn arg_fixed(args: *const wl_argument, index: isize) -> f64 {
+    arg_int(args, index) as f64 / 256.0
+}
+
+pub unsafe fn arg_object(args: *const wl_argument, index: isize) -> *mut wl_proxy {
+    *(args.offset(index) as *const *mut wl_proxy)
+}
+
+pub unsafe fn arg_string(args: *const wl_argument, index: isize) -> Option<String> {
+    let s = *(args.offset(index) as *const *const ::libc::c_char);
+    if s.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(s).to_string_lossy().into_owned())
+    }
+}
+
+pub unsafe fn arg_fd(args: *const wl_argument, index: isize) -> c_int {
+    *(args.offset(index) as *const c_int)
+}
@@ -0,0 +1,108 @@
+//! Interfaces of `pointer-gestures-unstable-v1` from wayland-protocols, at version 1.
+//!
+//! Generated from the XML description of the protocol.
+
+#![allow(dead_code)]
+
+use std::ptr;
+
+use wayland_sys::common::{wl_interface, wl_message};
+
+use super::Static;
+
+const NULL: *const wl_interface = 0 as *const wl_interface;
+
+static TYPES: Static<[*const wl_interface; 9]> = Static([
+    NULL,
+    NULL,
+    NULL,
+    NULL,
+    NULL,
+    &ZWP_POINTER_GESTURE_SWIPE_V1_INTERFACE.0 as *const wl_interface,
+    NULL,
+    &ZWP_POINTER_GESTURE_PINCH_V1_INTERFACE.0 as *const wl_interface,
+    NULL,
+]);
+
+static ZWP_POINTER_GESTURES_V1_REQUESTS: Static<[wl_message; 2]> = Static([
+    wl_message { name: c_str!("get_swipe_gesture"), signature: c_str!("no"), types: &TYPES.0[5] },
+    wl_message { name: c_str!("get_pinch_gesture"), signature: c_str!("no"), types: &TYPES.0[7] },
+]);
+
+pub static ZWP_POINTER_GESTURES_V1_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("zwp_pointer_gestures_v1"),
+    version: 1,
+    request_count: 2,
+    requests: &ZWP_POINTER_GESTURES_V1_REQUESTS.0 as *const wl_message,
+    event_count: 0,
+    events: ptr::null(),
+});
+
+pub mod zwp_pointer_gestures_v1 {
+    pub mod request {
+        pub const GET_SWIPE_GESTURE: u32 = 0;
+        pub const GET_PINCH_GESTURE: u32 = 1;
+    }
+}
+
+static ZWP_POINTER_GESTURE_SWIPE_V1_REQUESTS: Static<[wl_message; 1]> = Static([
+    wl_message { name: c_str!("destroy"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+static ZWP_POINTER_GESTURE_SWIPE_V1_EVENTS: Static<[wl_message; 3]> = Static([
+    wl_message { name: c_str!("begin"), signature: c_str!("uuou"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("update"), signature: c_str!("uff"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("end"), signature: c_str!("uui"), types: &TYPES.0[0] },
+]);
+
+pub static ZWP_POINTER_GESTURE_SWIPE_V1_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("zwp_pointer_gesture_swipe_v1"),
+    version: 1,
+    request_count: 1,
+    requests: &ZWP_POINTER_GESTURE_SWIPE_V1_REQUESTS.0 as *const wl_message,
+    event_count: 3,
+    events: &ZWP_POINTER_GESTURE_SWIPE_V1_EVENTS.0 as *const wl_message,
+});
+
+pub mod zwp_pointer_gesture_swipe_v1 {
+    pub mod request {
+        pub const DESTROY: u32 = 0;
+    }
+
+    pub mod event {
+        pub const BEGIN: u32 = 0;
+        pub const UPDATE: u32 = 1;
+        pub const END: u32 = 2;
+    }
+}
+
+static ZWP_POINTER_GESTURE_PINCH_V1_REQUESTS: Static<[wl_message; 1]> = Static([
+    wl_message { name: c_str!("destroy"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+static ZWP_POINTER_GESTURE_PINCH_V1_EVENTS: Static<[wl_message; 3]> = Static([
+    wl_message { name: c_str!("begin"), signature: c_str!("uuou"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("update"), signature: c_str!("uffff"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("end"), signature: c_str!("uui"), types: &TYPES.0[0] },
+]);
+
+pub static ZWP_POINTER_GESTURE_PINCH_V1_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("zwp_pointer_gesture_pinch_v1"),
+    version: 1,
+    request_count: 1,
+    requests: &ZWP_POINTER_GESTURE_PINCH_V1_REQUESTS.0 as *const wl_message,
+    event_count: 3,
+    events: &ZWP_POINTER_GESTURE_PINCH_V1_EVENTS.0 as *const wl_message,
+});
+
+pub mod zwp_pointer_gesture_pinch_v1 {
+    pub mod request {
+        pub const DESTROY: u32 = 0;
+    }
+
+    pub mod event {
+        pub const BEGIN: u32 = 0;
+        pub const UPDATE: u32 = 1;
+        pub const END: u32 = 2;
+    }
+}
@@ -0,0 +1,139 @@
+//! Interfaces of `primary-selection-unstable-v1` from wayland-protocols, at version 1.
+//!
+//! Generated from the XML description of the protocol.
+
+#![allow(dead_code)]
+
+use std::ptr;
+
+use wayland_sys::common::{wl_interface, wl_message};
+
+use super::Static;
+
+const NULL: *const wl_interface = 0 as *const wl_interface;
+
+static TYPES: Static<[*const wl_interface; 9]> = Static([
+    NULL,
+    NULL,
+    &ZWP_PRIMARY_SELECTION_SOURCE_V1_INTERFACE.0 as *const wl_interface,
+    &ZWP_PRIMARY_SELECTION_DEVICE_V1_INTERFACE.0 as *const wl_interface,
+    NULL,
+    &ZWP_PRIMARY_SELECTION_SOURCE_V1_INTERFACE.0 as *const wl_interface,
+    NULL,
+    &ZWP_PRIMARY_SELECTION_OFFER_V1_INTERFACE.0 as *const wl_interface,
+    &ZWP_PRIMARY_SELECTION_OFFER_V1_INTERFACE.0 as *const wl_interface,
+]);
+
+static ZWP_PRIMARY_SELECTION_DEVICE_MANAGER_V1_REQUESTS: Static<[wl_message; 3]> = Static([
+    wl_message { name: c_str!("create_source"), signature: c_str!("n"), types: &TYPES.0[2] },
+    wl_message { name: c_str!("get_device"), signature: c_str!("no"), types: &TYPES.0[3] },
+    wl_message { name: c_str!("destroy"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+pub static ZWP_PRIMARY_SELECTION_DEVICE_MANAGER_V1_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("zwp_primary_selection_device_manager_v1"),
+    version: 1,
+    request_count: 3,
+    requests: &ZWP_PRIMARY_SELECTION_DEVICE_MANAGER_V1_REQUESTS.0 as *const wl_message,
+    event_count: 0,
+    events: ptr::null(),
+});
+
+pub mod zwp_primary_selection_device_manager_v1 {
+    pub mod request {
+        pub const CREATE_SOURCE: u32 = 0;
+        pub const GET_DEVICE: u32 = 1;
+        pub const DESTROY: u32 = 2;
+    }
+}
+
+static ZWP_PRIMARY_SELECTION_DEVICE_V1_REQUESTS: Static<[wl_message; 2]> = Static([
+    wl_message { name: c_str!("set_selection"), signature: c_str!("?ou"), types: &TYPES.0[5] },
+    wl_message { name: c_str!("destroy"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+static ZWP_PRIMARY_SELECTION_DEVICE_V1_EVENTS: Static<[wl_message; 2]> = Static([
+    wl_message { name: c_str!("data_offer"), signature: c_str!("n"), types: &TYPES.0[7] },
+    wl_message { name: c_str!("selection"), signature: c_str!("?o"), types: &TYPES.0[8] },
+]);
+
+pub static ZWP_PRIMARY_SELECTION_DEVICE_V1_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("zwp_primary_selection_device_v1"),
+    version: 1,
+    request_count: 2,
+    requests: &ZWP_PRIMARY_SELECTION_DEVICE_V1_REQUESTS.0 as *const wl_message,
+    event_count: 2,
+    events: &ZWP_PRIMARY_SELECTION_DEVICE_V1_EVENTS.0 as *const wl_message,
+});
+
+pub mod zwp_primary_selection_device_v1 {
+    pub mod request {
+        pub const SET_SELECTION: u32 = 0;
+        pub const DESTROY: u32 = 1;
+    }
+
+    pub mod event {
+        pub const DATA_OFFER: u32 = 0;
+        pub const SELECTION: u32 = 1;
+    }
+}
+
+static ZWP_PRIMARY_SELECTION_OFFER_V1_REQUESTS: Static<[wl_message; 2]> = Static([
+    wl_message { name: c_str!("receive"), signature: c_str!("sh"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("destroy"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+static ZWP_PRIMARY_SELECTION_OFFER_V1_EVENTS: Static<[wl_message; 1]> = Static([
+    wl_message { name: c_str!("offer"), signature: c_str!("s"), types: &TYPES.0[0] },
+]);
+
+pub static ZWP_PRIMARY_SELECTION_OFFER_V1_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("zwp_primary_selection_offer_v1"),
+    version: 1,
+    request_count: 2,
+    requests: &ZWP_PRIMARY_SELECTION_OFFER_V1_REQUESTS.0 as *const wl_message,
+    event_count: 1,
+    events: &ZWP_PRIMARY_SELECTION_OFFER_V1_EVENTS.0 as *const wl_message,
+});
+
+pub mod zwp_primary_selection_offer_v1 {
+    pub mod request {
+        pub const RECEIVE: u32 = 0;
+        pub const DESTROY: u32 = 1;
+    }
+
+    pub mod event {
+        pub const OFFER: u32 = 0;
+    }
+}
+
+static ZWP_PRIMARY_SELECTION_SOURCE_V1_REQUESTS: Static<[wl_message; 2]> = Static([
+    wl_message { name: c_str!("offer"), signature: c_str!("s"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("destroy"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+static ZWP_PRIMARY_SELECTION_SOURCE_V1_EVENTS: Static<[wl_message; 2]> = Static([
+    wl_message { name: c_str!("send"), signature: c_str!("sh"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("cancelled"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+pub static ZWP_PRIMARY_SELECTION_SOURCE_V1_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("zwp_primary_selection_source_v1"),
+    version: 1,
+    request_count: 2,
+    requests: &ZWP_PRIMARY_SELECTION_SOURCE_V1_REQUESTS.0 as *const wl_message,
+    event_count: 2,
+    events: &ZWP_PRIMARY_SELECTION_SOURCE_V1_EVENTS.0 as *const wl_message,
+});
+
+pub mod zwp_primary_selection_source_v1 {
+    pub mod request {
+        pub const OFFER: u32 = 0;
+        pub const DESTROY: u32 = 1;
+    }
+
+    pub mod event {
+        pub const SEND: u32 = 0;
+        pub const CANCELLED: u32 = 1;
+    }
+}
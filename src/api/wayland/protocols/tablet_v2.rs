@@ -0,0 +1,378 @@
+//! Interfaces of `tablet-v2` from wayland-protocols, at version 1.
+//!
+//! Generated from the XML description of the protocol.
+
+#![allow(dead_code)]
+
+use std::ptr;
+
+use wayland_sys::common::{wl_interface, wl_message};
+
+use super::Static;
+
+const NULL: *const wl_interface = 0 as *const wl_interface;
+
+static TYPES: Static<[*const wl_interface; 18]> = Static([
+    NULL,
+    NULL,
+    NULL,
+    NULL,
+    &ZWP_TABLET_SEAT_V2_INTERFACE.0 as *const wl_interface,
+    NULL,
+    &ZWP_TABLET_V2_INTERFACE.0 as *const wl_interface,
+    &ZWP_TABLET_TOOL_V2_INTERFACE.0 as *const wl_interface,
+    &ZWP_TABLET_PAD_V2_INTERFACE.0 as *const wl_interface,
+    NULL,
+    &ZWP_TABLET_V2_INTERFACE.0 as *const wl_interface,
+    NULL,
+    &ZWP_TABLET_PAD_RING_V2_INTERFACE.0 as *const wl_interface,
+    &ZWP_TABLET_PAD_STRIP_V2_INTERFACE.0 as *const wl_interface,
+    &ZWP_TABLET_PAD_GROUP_V2_INTERFACE.0 as *const wl_interface,
+    NULL,
+    &ZWP_TABLET_V2_INTERFACE.0 as *const wl_interface,
+    NULL,
+]);
+
+static ZWP_TABLET_MANAGER_V2_REQUESTS: Static<[wl_message; 2]> = Static([
+    wl_message { name: c_str!("get_tablet_seat"), signature: c_str!("no"), types: &TYPES.0[4] },
+    wl_message { name: c_str!("destroy"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+pub static ZWP_TABLET_MANAGER_V2_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("zwp_tablet_manager_v2"),
+    version: 1,
+    request_count: 2,
+    requests: &ZWP_TABLET_MANAGER_V2_REQUESTS.0 as *const wl_message,
+    event_count: 0,
+    events: ptr::null(),
+});
+
+pub mod zwp_tablet_manager_v2 {
+    pub mod request {
+        pub const GET_TABLET_SEAT: u32 = 0;
+        pub const DESTROY: u32 = 1;
+    }
+}
+
+static ZWP_TABLET_SEAT_V2_REQUESTS: Static<[wl_message; 1]> = Static([
+    wl_message { name: c_str!("destroy"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+static ZWP_TABLET_SEAT_V2_EVENTS: Static<[wl_message; 3]> = Static([
+    wl_message { name: c_str!("tablet_added"), signature: c_str!("n"), types: &TYPES.0[6] },
+    wl_message { name: c_str!("tool_added"), signature: c_str!("n"), types: &TYPES.0[7] },
+    wl_message { name: c_str!("pad_added"), signature: c_str!("n"), types: &TYPES.0[8] },
+]);
+
+pub static ZWP_TABLET_SEAT_V2_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("zwp_tablet_seat_v2"),
+    version: 1,
+    request_count: 1,
+    requests: &ZWP_TABLET_SEAT_V2_REQUESTS.0 as *const wl_message,
+    event_count: 3,
+    events: &ZWP_TABLET_SEAT_V2_EVENTS.0 as *const wl_message,
+});
+
+pub mod zwp_tablet_seat_v2 {
+    pub mod request {
+        pub const DESTROY: u32 = 0;
+    }
+
+    pub mod event {
+        pub const TABLET_ADDED: u32 = 0;
+        pub const TOOL_ADDED: u32 = 1;
+        pub const PAD_ADDED: u32 = 2;
+    }
+}
+
+static ZWP_TABLET_TOOL_V2_REQUESTS: Static<[wl_message; 2]> = Static([
+    wl_message { name: c_str!("set_cursor"), signature: c_str!("u?oii"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("destroy"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+static ZWP_TABLET_TOOL_V2_EVENTS: Static<[wl_message; 19]> = Static([
+    wl_message { name: c_str!("type"), signature: c_str!("u"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("hardware_serial"), signature: c_str!("uu"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("hardware_id_wacom"), signature: c_str!("uu"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("capability"), signature: c_str!("u"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("done"), signature: c_str!(""), types: &TYPES.0[0] },
+    wl_message { name: c_str!("removed"), signature: c_str!(""), types: &TYPES.0[0] },
+    wl_message { name: c_str!("proximity_in"), signature: c_str!("uoo"), types: &TYPES.0[9] },
+    wl_message { name: c_str!("proximity_out"), signature: c_str!(""), types: &TYPES.0[0] },
+    wl_message { name: c_str!("down"), signature: c_str!("u"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("up"), signature: c_str!(""), types: &TYPES.0[0] },
+    wl_message { name: c_str!("motion"), signature: c_str!("ff"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("pressure"), signature: c_str!("u"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("distance"), signature: c_str!("u"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("tilt"), signature: c_str!("ff"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("rotation"), signature: c_str!("f"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("slider"), signature: c_str!("i"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("wheel"), signature: c_str!("fi"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("button"), signature: c_str!("uuu"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("frame"), signature: c_str!("u"), types: &TYPES.0[0] },
+]);
+
+pub static ZWP_TABLET_TOOL_V2_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("zwp_tablet_tool_v2"),
+    version: 1,
+    request_count: 2,
+    requests: &ZWP_TABLET_TOOL_V2_REQUESTS.0 as *const wl_message,
+    event_count: 19,
+    events: &ZWP_TABLET_TOOL_V2_EVENTS.0 as *const wl_message,
+});
+
+pub mod zwp_tablet_tool_v2 {
+    pub mod request {
+        pub const SET_CURSOR: u32 = 0;
+        pub const DESTROY: u32 = 1;
+    }
+
+    pub mod event {
+        pub const TYPE: u32 = 0;
+        pub const HARDWARE_SERIAL: u32 = 1;
+        pub const HARDWARE_ID_WACOM: u32 = 2;
+        pub const CAPABILITY: u32 = 3;
+        pub const DONE: u32 = 4;
+        pub const REMOVED: u32 = 5;
+        pub const PROXIMITY_IN: u32 = 6;
+        pub const PROXIMITY_OUT: u32 = 7;
+        pub const DOWN: u32 = 8;
+        pub const UP: u32 = 9;
+        pub const MOTION: u32 = 10;
+        pub const PRESSURE: u32 = 11;
+        pub const DISTANCE: u32 = 12;
+        pub const TILT: u32 = 13;
+        pub const ROTATION: u32 = 14;
+        pub const SLIDER: u32 = 15;
+        pub const WHEEL: u32 = 16;
+        pub const BUTTON: u32 = 17;
+        pub const FRAME: u32 = 18;
+    }
+
+    pub mod type_ {
+        pub const PEN: u32 = 0x140;
+        pub const ERASER: u32 = 0x141;
+        pub const BRUSH: u32 = 0x142;
+        pub const PENCIL: u32 = 0x143;
+        pub const AIRBRUSH: u32 = 0x144;
+        pub const FINGER: u32 = 0x145;
+        pub const MOUSE: u32 = 0x146;
+        pub const LENS: u32 = 0x147;
+    }
+
+    pub mod capability {
+        pub const TILT: u32 = 1;
+        pub const PRESSURE: u32 = 2;
+        pub const DISTANCE: u32 = 3;
+        pub const ROTATION: u32 = 4;
+        pub const SLIDER: u32 = 5;
+        pub const WHEEL: u32 = 6;
+    }
+
+    pub mod button_state {
+        pub const RELEASED: u32 = 0;
+        pub const PRESSED: u32 = 1;
+    }
+
+    pub mod error {
+        pub const ROLE: u32 = 0;
+    }
+}
+
+static ZWP_TABLET_V2_REQUESTS: Static<[wl_message; 1]> = Static([
+    wl_message { name: c_str!("destroy"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+static ZWP_TABLET_V2_EVENTS: Static<[wl_message; 5]> = Static([
+    wl_message { name: c_str!("name"), signature: c_str!("s"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("id"), signature: c_str!("uu"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("path"), signature: c_str!("s"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("done"), signature: c_str!(""), types: &TYPES.0[0] },
+    wl_message { name: c_str!("removed"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+pub static ZWP_TABLET_V2_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("zwp_tablet_v2"),
+    version: 1,
+    request_count: 1,
+    requests: &ZWP_TABLET_V2_REQUESTS.0 as *const wl_message,
+    event_count: 5,
+    events: &ZWP_TABLET_V2_EVENTS.0 as *const wl_message,
+});
+
+pub mod zwp_tablet_v2 {
+    pub mod request {
+        pub const DESTROY: u32 = 0;
+    }
+
+    pub mod event {
+        pub const NAME: u32 = 0;
+        pub const ID: u32 = 1;
+        pub const PATH: u32 = 2;
+        pub const DONE: u32 = 3;
+        pub const REMOVED: u32 = 4;
+    }
+}
+
+static ZWP_TABLET_PAD_RING_V2_REQUESTS: Static<[wl_message; 2]> = Static([
+    wl_message { name: c_str!("set_feedback"), signature: c_str!("su"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("destroy"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+static ZWP_TABLET_PAD_RING_V2_EVENTS: Static<[wl_message; 4]> = Static([
+    wl_message { name: c_str!("source"), signature: c_str!("u"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("angle"), signature: c_str!("f"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("stop"), signature: c_str!(""), types: &TYPES.0[0] },
+    wl_message { name: c_str!("frame"), signature: c_str!("u"), types: &TYPES.0[0] },
+]);
+
+pub static ZWP_TABLET_PAD_RING_V2_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("zwp_tablet_pad_ring_v2"),
+    version: 1,
+    request_count: 2,
+    requests: &ZWP_TABLET_PAD_RING_V2_REQUESTS.0 as *const wl_message,
+    event_count: 4,
+    events: &ZWP_TABLET_PAD_RING_V2_EVENTS.0 as *const wl_message,
+});
+
+pub mod zwp_tablet_pad_ring_v2 {
+    pub mod request {
+        pub const SET_FEEDBACK: u32 = 0;
+        pub const DESTROY: u32 = 1;
+    }
+
+    pub mod event {
+        pub const SOURCE: u32 = 0;
+        pub const ANGLE: u32 = 1;
+        pub const STOP: u32 = 2;
+        pub const FRAME: u32 = 3;
+    }
+
+    pub mod source {
+        pub const FINGER: u32 = 1;
+    }
+}
+
+static ZWP_TABLET_PAD_STRIP_V2_REQUESTS: Static<[wl_message; 2]> = Static([
+    wl_message { name: c_str!("set_feedback"), signature: c_str!("su"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("destroy"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+static ZWP_TABLET_PAD_STRIP_V2_EVENTS: Static<[wl_message; 4]> = Static([
+    wl_message { name: c_str!("source"), signature: c_str!("u"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("position"), signature: c_str!("u"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("stop"), signature: c_str!(""), types: &TYPES.0[0] },
+    wl_message { name: c_str!("frame"), signature: c_str!("u"), types: &TYPES.0[0] },
+]);
+
+pub static ZWP_TABLET_PAD_STRIP_V2_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("zwp_tablet_pad_strip_v2"),
+    version: 1,
+    request_count: 2,
+    requests: &ZWP_TABLET_PAD_STRIP_V2_REQUESTS.0 as *const wl_message,
+    event_count: 4,
+    events: &ZWP_TABLET_PAD_STRIP_V2_EVENTS.0 as *const wl_message,
+});
+
+pub mod zwp_tablet_pad_strip_v2 {
+    pub mod request {
+        pub const SET_FEEDBACK: u32 = 0;
+        pub const DESTROY: u32 = 1;
+    }
+
+    pub mod event {
+        pub const SOURCE: u32 = 0;
+        pub const POSITION: u32 = 1;
+        pub const STOP: u32 = 2;
+        pub const FRAME: u32 = 3;
+    }
+
+    pub mod source {
+        pub const FINGER: u32 = 1;
+    }
+}
+
+static ZWP_TABLET_PAD_GROUP_V2_REQUESTS: Static<[wl_message; 1]> = Static([
+    wl_message { name: c_str!("destroy"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+static ZWP_TABLET_PAD_GROUP_V2_EVENTS: Static<[wl_message; 6]> = Static([
+    wl_message { name: c_str!("buttons"), signature: c_str!("a"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("ring"), signature: c_str!("n"), types: &TYPES.0[12] },
+    wl_message { name: c_str!("strip"), signature: c_str!("n"), types: &TYPES.0[13] },
+    wl_message { name: c_str!("modes"), signature: c_str!("u"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("done"), signature: c_str!(""), types: &TYPES.0[0] },
+    wl_message { name: c_str!("mode_switch"), signature: c_str!("uuu"), types: &TYPES.0[0] },
+]);
+
+pub static ZWP_TABLET_PAD_GROUP_V2_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("zwp_tablet_pad_group_v2"),
+    version: 1,
+    request_count: 1,
+    requests: &ZWP_TABLET_PAD_GROUP_V2_REQUESTS.0 as *const wl_message,
+    event_count: 6,
+    events: &ZWP_TABLET_PAD_GROUP_V2_EVENTS.0 as *const wl_message,
+});
+
+pub mod zwp_tablet_pad_group_v2 {
+    pub mod request {
+        pub const DESTROY: u32 = 0;
+    }
+
+    pub mod event {
+        pub const BUTTONS: u32 = 0;
+        pub const RING: u32 = 1;
+        pub const STRIP: u32 = 2;
+        pub const MODES: u32 = 3;
+        pub const DONE: u32 = 4;
+        pub const MODE_SWITCH: u32 = 5;
+    }
+}
+
+static ZWP_TABLET_PAD_V2_REQUESTS: Static<[wl_message; 2]> = Static([
+    wl_message { name: c_str!("set_feedback"), signature: c_str!("usu"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("destroy"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+static ZWP_TABLET_PAD_V2_EVENTS: Static<[wl_message; 8]> = Static([
+    wl_message { name: c_str!("group"), signature: c_str!("n"), types: &TYPES.0[14] },
+    wl_message { name: c_str!("path"), signature: c_str!("s"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("buttons"), signature: c_str!("u"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("done"), signature: c_str!(""), types: &TYPES.0[0] },
+    wl_message { name: c_str!("button"), signature: c_str!("uuu"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("enter"), signature: c_str!("uoo"), types: &TYPES.0[15] },
+    wl_message { name: c_str!("leave"), signature: c_str!("uo"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("removed"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+pub static ZWP_TABLET_PAD_V2_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("zwp_tablet_pad_v2"),
+    version: 1,
+    request_count: 2,
+    requests: &ZWP_TABLET_PAD_V2_REQUESTS.0 as *const wl_message,
+    event_count: 8,
+    events: &ZWP_TABLET_PAD_V2_EVENTS.0 as *const wl_message,
+});
+
+pub mod zwp_tablet_pad_v2 {
+    pub mod request {
+        pub const SET_FEEDBACK: u32 = 0;
+        pub const DESTROY: u32 = 1;
+    }
+
+    pub mod event {
+        pub const GROUP: u32 = 0;
+        pub const PATH: u32 = 1;
+        pub const BUTTONS: u32 = 2;
+        pub const DONE: u32 = 3;
+        pub const BUTTON: u32 = 4;
+        pub const ENTER: u32 = 5;
+        pub const LEAVE: u32 = 6;
+        pub const REMOVED: u32 = 7;
+    }
+
+    pub mod button_state {
+        pub const RELEASED: u32 = 0;
+        pub const PRESSED: u32 = 1;
+    }
+}
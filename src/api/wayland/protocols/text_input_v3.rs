@@ -0,0 +1,140 @@
+//! Interfaces of `text-input-unstable-v3` from wayland-protocols, at version 1.
+//!
+//! Generated from the XML description of the protocol.
+
+#![allow(dead_code)]
+
+use std::ptr;
+
+use wayland_sys::common::{wl_interface, wl_message};
+
+use super::Static;
+
+const NULL: *const wl_interface = 0 as *const wl_interface;
+
+static TYPES: Static<[*const wl_interface; 6]> = Static([
+    NULL,
+    NULL,
+    NULL,
+    NULL,
+    &ZWP_TEXT_INPUT_V3_INTERFACE.0 as *const wl_interface,
+    NULL,
+]);
+
+static ZWP_TEXT_INPUT_V3_REQUESTS: Static<[wl_message; 8]> = Static([
+    wl_message { name: c_str!("destroy"), signature: c_str!(""), types: &TYPES.0[0] },
+    wl_message { name: c_str!("enable"), signature: c_str!(""), types: &TYPES.0[0] },
+    wl_message { name: c_str!("disable"), signature: c_str!(""), types: &TYPES.0[0] },
+    wl_message { name: c_str!("set_surrounding_text"), signature: c_str!("sii"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("set_text_change_cause"), signature: c_str!("u"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("set_content_type"), signature: c_str!("uu"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("set_cursor_rectangle"), signature: c_str!("iiii"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("commit"), signature: c_str!(""), types: &TYPES.0[0] },
+]);
+
+static ZWP_TEXT_INPUT_V3_EVENTS: Static<[wl_message; 6]> = Static([
+    wl_message { name: c_str!("enter"), signature: c_str!("o"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("leave"), signature: c_str!("o"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("preedit_string"), signature: c_str!("?sii"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("commit_string"), signature: c_str!("?s"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("delete_surrounding_text"), signature: c_str!("uu"), types: &TYPES.0[0] },
+    wl_message { name: c_str!("done"), signature: c_str!("u"), types: &TYPES.0[0] },
+]);
+
+pub static ZWP_TEXT_INPUT_V3_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("zwp_text_input_v3"),
+    version: 1,
+    request_count: 8,
+    requests: &ZWP_TEXT_INPUT_V3_REQUESTS.0 as *const wl_message,
+    event_count: 6,
+    events: &ZWP_TEXT_INPUT_V3_EVENTS.0 as *const wl_message,
+});
+
+pub mod zwp_text_input_v3 {
+    pub mod request {
+        pub const DESTROY: u32 = 0;
+        pub const ENABLE: u32 = 1;
+        pub const DISABLE: u32 = 2;
+        pub const SET_SURROUNDING_TEXT: u32 = 3;
+        pub const SET_TEXT_CHANGE_CAUSE: u32 = 4;
+        pub const SET_CONTENT_TYPE: u32 = 5;
+        pub const SET_CURSOR_RECTANGLE: u32 = 6;
+        pub const COMMIT: u32 = 7;
+    }
+
+    pub mod event {
+        pub const ENTER: u32 = 0;
+        pub const LEAVE: u32 = 1;
+        pub const PREEDIT_STRING: u32 = 2;
+        pub const COMMIT_STRING: u32 = 3;
+        pub const DELETE_SURROUNDING_TEXT: u32 = 4;
+        pub const DONE: u32 = 5;
+    }
+
+    pub mod change_cause {
+        pub const INPUT_METHOD: u32 = 0;
+        pub const OTHER: u32 = 1;
+    }
+
+    pub mod content_hint {
+        pub const NONE: u32 = 0x0;
+        pub const COMPLETION: u32 = 0x1;
+        pub const SPELLCHECK: u32 = 0x2;
+        pub const AUTO_CAPITALIZATION: u32 = 0x4;
+        pub const LOWERCASE: u32 = 0x8;
+        pub const UPPERCASE: u32 = 0x10;
+        pub const TITLECASE: u32 = 0x20;
+        pub const HIDDEN_TEXT: u32 = 0x40;
+        pub const SENSITIVE_DATA: u32 = 0x80;
+        pub const LATIN: u32 = 0x100;
+        pub const MULTILINE: u32 = 0x200;
+    }
+
+    pub mod content_purpose {
+        pub const NORMAL: u32 = 0;
+        pub const ALPHA: u32 = 1;
+        pub const DIGITS: u32 = 2;
+        pub const NUMBER: u32 = 3;
+        pub const PHONE: u32 = 4;
+        pub const URL: u32 = 5;
+        pub const EMAIL: u32 = 6;
+        pub const NAME: u32 = 7;
+        pub const PASSWORD: u32 = 8;
+        pub const PIN: u32 = 9;
+        pub const DATE: u32 = 10;
+        pub const TIME: u32 = 11;
+        pub const DATETIME: u32 = 12;
+        pub const TERMINAL: u32 = 13;
+    }
+
+    pub mod preedit_hint {
+        pub const WHOLE: u32 = 1;
+        pub const SELECTION: u32 = 2;
+        pub const PREDICTION: u32 = 3;
+        pub const PREFIX: u32 = 4;
+        pub const SUFFIX: u32 = 5;
+        pub const SPELLING_ERROR: u32 = 6;
+        pub const COMPOSE_ERROR: u32 = 7;
+    }
+}
+
+static ZWP_TEXT_INPUT_MANAGER_V3_REQUESTS: Static<[wl_message; 2]> = Static([
+    wl_message { name: c_str!("destroy"), signature: c_str!(""), types: &TYPES.0[0] },
+    wl_message { name: c_str!("get_text_input"), signature: c_str!("no"), types: &TYPES.0[4] },
+]);
+
+pub static ZWP_TEXT_INPUT_MANAGER_V3_INTERFACE: Static<wl_interface> = Static(wl_interface {
+    name: c_str!("zwp_text_input_manager_v3"),
+    version: 1,
+    request_count: 2,
+    requests: &ZWP_TEXT_INPUT_MANAGER_V3_REQUESTS.0 as *const wl_message,
+    event_count: 0,
+    events: ptr::null(),
+});
+
+pub mod zwp_text_input_manager_v3 {
+    pub mod request {
+        pub const DESTROY: u32 = 0;
+        pub const GET_TEXT_INPUT: u32 = 1;
+    }
+}
@@ -0,0 +1,289 @@
+//! Pens of graphics tablets, through the tablet protocol.
+//!
+//! The compositor announces each tool of the tablets of the seat, then sends the state of a
+//! tool over a surface in frames. The state is gathered until the end of a frame, which is
+//! when the `Pen` events are generated.
+
+use std::mem;
+use std::ptr;
+use std::sync::Mutex;
+
+use libc::c_void;
+
+use wayland_sys::client::wl_proxy;
+use wayland_sys::common::{wl_argument, wl_message};
+
+use events::{ElementState, Pen, PenPhase, PenTool};
+use Event as GlutinEvent;
+
+use super::protocols::{self, Registry};
+use super::protocols::tablet_v2::{ZWP_TABLET_MANAGER_V2_INTERFACE, ZWP_TABLET_SEAT_V2_INTERFACE};
+use super::protocols::tablet_v2::zwp_tablet_manager_v2 as manager;
+use super::protocols::tablet_v2::zwp_tablet_pad_v2 as pad;
+use super::protocols::tablet_v2::zwp_tablet_seat_v2 as seat;
+use super::protocols::tablet_v2::zwp_tablet_tool_v2 as tool;
+use super::protocols::tablet_v2::zwp_tablet_v2 as tablet;
+
+/// Linux event codes of the barrel buttons, in the order of the `Button` phase numbers.
+const BUTTONS: [u32; 3] = [0x14b /* BTN_STYLUS */, 0x14c /* BTN_STYLUS2 */, 0x149 /* BTN_STYLUS3 */];
+
+/// Tilt in degrees that is reported as 1.
+///
+/// The compositor sends the tilt in degrees without its range, this is about the largest tilt
+/// that the pens of common tablets can measure.
+const MAX_TILT: f64 = 60.0;
+
+/// Pressure and distance are sent between 0 and this value.
+const AXIS_MAX: f64 = 65535.0;
+
+pub struct Tablets {
+    seat: *mut wl_proxy,
+    state: Box<Mutex<TabletState>>
+}
+
+// the objects are only used while the state is locked, or when dispatching events
+unsafe impl Send for Tablets {}
+unsafe impl Sync for Tablets {}
+
+struct TabletState {
+    tools: Vec<Tool>,
+    /// Events generated by the tools, along with the surface they happened over
+    events: Vec<(GlutinEvent, *mut wl_proxy)>
+}
+
+struct Tool {
+    proxy: *mut wl_proxy,
+    kind: PenTool,
+    id: u64,
+    has_distance: bool,
+    /// Surface the tool is over, if any
+    surface: Option<*mut wl_proxy>,
+    location: (f64, f64),
+    pressure: f64,
+    tilt: (f64, f64),
+    distance: f64,
+    /// Phases received since the last frame
+    phases: Vec<PenPhase>,
+    /// Whether an axis changed since the last frame
+    moved: bool
+}
+
+impl Tablets {
+    /// Gets the tablets of a seat, if the compositor supports the protocol.
+    pub fn new(registry: &Registry, wl_seat: *mut wl_proxy) -> Option<Tablets> {
+        unsafe {
+            let manager = match registry.bind(&ZWP_TABLET_MANAGER_V2_INTERFACE) {
+                Some(manager) => manager,
+                None => return None
+            };
+            let seat = protocols::create_for(manager, manager::request::GET_TABLET_SEAT,
+                                             &ZWP_TABLET_SEAT_V2_INTERFACE, wl_seat);
+            // the objects created from the manager outlive it
+            protocols::destroy(manager, manager::request::DESTROY);
+            if seat.is_null() {
+                return None;
+            }
+
+            let state = Box::new(Mutex::new(TabletState {
+                tools: Vec::new(),
+                events: Vec::new()
+            }));
+            protocols::set_dispatcher(seat, seat_dispatcher, &*state as *const _ as *mut c_void);
+
+            Some(Tablets {
+                seat: seat,
+                state: state
+            })
+        }
+    }
+
+    /// Returns the events generated while dispatching, with the surface they are for.
+    pub fn take_events(&self) -> Vec<(GlutinEvent, *mut wl_proxy)> {
+        mem::replace(&mut self.state.lock().unwrap().events, Vec::new())
+    }
+}
+
+impl Drop for Tablets {
+    fn drop(&mut self) {
+        unsafe { protocols::destroy(self.seat, seat::request::DESTROY) };
+    }
+}
+
+impl TabletState {
+    fn tool_event(&mut self, proxy: *mut wl_proxy, opcode: u32, args: *const wl_argument) {
+        if opcode == tool::event::REMOVED {
+            self.tools.retain(|t| t.proxy != proxy);
+            unsafe { protocols::destroy(proxy, tool::request::DESTROY) };
+            return;
+        }
+
+        let TabletState { ref mut tools, ref mut events } = *self;
+        let tool = match tools.iter_mut().find(|t| t.proxy == proxy) {
+            Some(tool) => tool,
+            None => return
+        };
+
+        unsafe {
+            match opcode {
+                tool::event::TYPE => {
+                    tool.kind = match protocols::arg_uint(args, 0) {
+                        tool::type_::ERASER => PenTool::Eraser,
+                        _ => PenTool::Pen
+                    };
+                },
+                tool::event::HARDWARE_SERIAL => {
+                    let high = protocols::arg_uint(args, 0) as u64;
+                    let low = protocols::arg_uint(args, 1) as u64;
+                    tool.id = high << 32 | low;
+                },
+                tool::event::CAPABILITY => {
+                    if protocols::arg_uint(args, 0) == tool::capability::DISTANCE {
+                        tool.has_distance = true;
+                    }
+                },
+                tool::event::PROXIMITY_IN => {
+                    tool.surface = Some(protocols::arg_object(args, 2));
+                    tool.phases.push(PenPhase::ProximityIn);
+                },
+                tool::event::PROXIMITY_OUT => tool.phases.push(PenPhase::ProximityOut),
+                tool::event::DOWN => tool.phases.push(PenPhase::Down),
+                tool::event::UP => {
+                    tool.pressure = 0.0;
+                    tool.phases.push(PenPhase::Up);
+                },
+                tool::event::MOTION => {
+                    tool.location = (protocols::arg_fixed(args, 0), protocols::arg_fixed(args, 1));
+                    tool.moved = true;
+                },
+                tool::event::PRESSURE => {
+                    tool.pressure = protocols::arg_uint(args, 0) as f64 / AXIS_MAX;
+                    tool.moved = true;
+                },
+                tool::event::DISTANCE => {
+                    tool.distance = protocols::arg_uint(args, 0) as f64 / AXIS_MAX;
+                    tool.moved = true;
+                },
+                tool::event::TILT => {
+                    let normalize = |degrees: f64| (degrees / MAX_TILT).max(-1.0).min(1.0);
+                    tool.tilt = (normalize(protocols::arg_fixed(args, 0)),
+                                 normalize(protocols::arg_fixed(args, 1)));
+                    tool.moved = true;
+                },
+                tool::event::BUTTON => {
+                    let button = protocols::arg_uint(args, 1);
+                    let state = match protocols::arg_uint(args, 2) {
+                        tool::button_state::PRESSED => ElementState::Pressed,
+                        _ => ElementState::Released
+                    };
+                    if let Some(index) = BUTTONS.iter().position(|&b| b == button) {
+                        tool.phases.push(PenPhase::Button(state, index as u8 + 1));
+                    }
+                },
+                tool::event::FRAME => tool.frame(events),
+                _ => ()
+            }
+        }
+    }
+}
+
+impl Tool {
+    fn new(proxy: *mut wl_proxy) -> Tool {
+        Tool {
+            proxy: proxy,
+            kind: PenTool::Pen,
+            // replaced by the serial number of the tool, if it has one
+            id: unsafe { protocols::id(proxy) } as u64,
+            has_distance: false,
+            surface: None,
+            location: (0.0, 0.0),
+            pressure: 0.0,
+            tilt: (0.0, 0.0),
+            distance: 0.0,
+            phases: Vec::new(),
+            moved: false
+        }
+    }
+
+    /// Generates the events of the frame that just ended.
+    fn frame(&mut self, events: &mut Vec<(GlutinEvent, *mut wl_proxy)>) {
+        let mut phases = mem::replace(&mut self.phases, Vec::new());
+        if phases.is_empty() && self.moved {
+            phases.push(PenPhase::Moved);
+        }
+        self.moved = false;
+
+        let surface = match self.surface {
+            Some(surface) => surface,
+            None => return
+        };
+        for phase in phases {
+            events.push((GlutinEvent::Pen(Pen {
+                phase: phase,
+                tool: self.kind,
+                location: self.location,
+                pressure: self.pressure,
+                tilt: self.tilt,
+                distance: if self.has_distance { Some(self.distance) } else { None },
+                id: self.id
+            }), surface));
+            if phase == PenPhase::ProximityOut {
+                self.surface = None;
+            }
+        }
+    }
+}
+
+extern "C" fn seat_dispatcher(_: *const c_void, proxy: *mut c_void, opcode: u32,
+                              _: *const wl_message, args: *const wl_argument)
+{
+    unsafe {
+        // the new objects already exist, they only need to be handled
+        let object = protocols::arg_object(args, 0);
+        match opcode {
+            seat::event::TABLET_ADDED => {
+                protocols::set_dispatcher(object, tablet_dispatcher, ptr::null_mut());
+            },
+            seat::event::TOOL_ADDED => {
+                let data = protocols::user_data(proxy);
+                let state = &*(data as *const Mutex<TabletState>);
+                state.lock().unwrap().tools.push(Tool::new(object));
+                protocols::set_dispatcher(object, tool_dispatcher, data);
+            },
+            seat::event::PAD_ADDED => {
+                protocols::set_dispatcher(object, pad_dispatcher, ptr::null_mut());
+            },
+            _ => ()
+        }
+    }
+}
+
+extern "C" fn tool_dispatcher(_: *const c_void, proxy: *mut c_void, opcode: u32,
+                              _: *const wl_message, args: *const wl_argument)
+{
+    unsafe {
+        let state = &*(protocols::user_data(proxy) as *const Mutex<TabletState>);
+        state.lock().unwrap().tool_event(proxy as *mut wl_proxy, opcode, args);
+    }
+}
+
+// Only the tools are used, the tablets and the pads are destroyed once they are unplugged.
+
+extern "C" fn tablet_dispatcher(_: *const c_void, proxy: *mut c_void, opcode: u32,
+                                _: *const wl_message, _: *const wl_argument)
+{
+    unsafe {
+        if opcode == tablet::event::REMOVED {
+            protocols::destroy(proxy as *mut wl_proxy, tablet::request::DESTROY);
+        }
+    }
+}
+
+extern "C" fn pad_dispatcher(_: *const c_void, proxy: *mut c_void, opcode: u32,
+                             _: *const wl_message, _: *const wl_argument)
+{
+    unsafe {
+        if opcode == pad::event::REMOVED {
+            protocols::destroy(proxy as *mut wl_proxy, pad::request::DESTROY);
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! Input methods, through the text input protocol.
+//!
+//! The input method of the seat composes text in the surface that has the keyboard focus, once
+//! the text input is enabled for it. The changes of the composition are sent in batches which
+//! end with a `done` event, and are only applied then.
+
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Mutex;
+
+use libc::c_void;
+
+use wayland_sys::client::*;
+use wayland_sys::common::{wl_argument, wl_message};
+
+use Event as GlutinEvent;
+
+use super::protocols::{self, Registry};
+use super::protocols::text_input_v3::{ZWP_TEXT_INPUT_MANAGER_V3_INTERFACE, ZWP_TEXT_INPUT_V3_INTERFACE};
+use super::protocols::text_input_v3::zwp_text_input_manager_v3 as manager;
+use super::protocols::text_input_v3::zwp_text_input_v3 as text_input;
+
+pub struct TextInput {
+    state: Box<Mutex<TextInputState>>
+}
+
+// the objects are only used while the state is locked, or when dispatching events
+unsafe impl Send for TextInput {}
+unsafe impl Sync for TextInput {}
+
+struct TextInputState {
+    proxy: *mut wl_proxy,
+    /// Surface with the keyboard focus
+    focus: Option<*mut wl_proxy>,
+    /// Settings of the surfaces, those that aren't there use the default ones
+    surfaces: HashMap<usize, SurfaceSettings>,
+    enabled: bool,
+    /// Whether a composition is in progress
+    composing: bool,
+    /// Preedit text and cursor received since the last `done` event
+    pending_preedit: Option<(String, Option<(usize, usize)>)>,
+    /// Text committed since the last `done` event
+    pending_commit: Option<String>,
+    /// Events of the input method, along with the surface they are for
+    events: Vec<(GlutinEvent, *mut wl_proxy)>
+}
+
+#[derive(Clone, Copy)]
+struct SurfaceSettings {
+    allowed: bool,
+    caret: Option<(i32, i32)>
+}
+
+impl Default for SurfaceSettings {
+    fn default() -> SurfaceSettings {
+        SurfaceSettings {
+            allowed: true,
+            caret: None
+        }
+    }
+}
+
+impl TextInput {
+    /// Gets the text input of a seat, if the compositor supports the protocol.
+    pub fn new(registry: &Registry, wl_seat: *mut wl_proxy) -> Option<TextInput> {
+        unsafe {
+            let manager = match registry.bind(&ZWP_TEXT_INPUT_MANAGER_V3_INTERFACE) {
+                Some(manager) => manager,
+                None => return None
+            };
+            let proxy = protocols::create_for(manager, manager::request::GET_TEXT_INPUT,
+                                              &ZWP_TEXT_INPUT_V3_INTERFACE, wl_seat);
+            // the objects created from the manager outlive it
+            protocols::destroy(manager, manager::request::DESTROY);
+            if proxy.is_null() {
+                return None;
+            }
+
+            let state = Box::new(Mutex::new(TextInputState {
+                proxy: proxy,
+                focus: None,
+                surfaces: HashMap::new(),
+                enabled: false,
+                composing: false,
+                pending_preedit: None,
+                pending_commit: None,
+                events: Vec::new()
+            }));
+            protocols::set_dispatcher(proxy, dispatcher, &*state as *const _ as *mut c_void);
+
+            Some(TextInput {
+                state: state
+            })
+        }
+    }
+
+    /// Sets whether the input method can be used in a surface.
+    pub fn set_allowed(&self, surface: *mut wl_proxy, allowed: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.surfaces.entry(surface as usize).or_insert_with(SurfaceSettings::default).allowed = allowed;
+        if state.focus == Some(surface) {
+            state.update();
+        }
+    }
+
+    /// Sets the position of the caret in a surface, next to which the input method shows its
+    /// candidates.
+    pub fn set_caret(&self, surface: *mut wl_proxy, x: i32, y: i32) {
+        let mut state = self.state.lock().unwrap();
+        state.surfaces.entry(surface as usize).or_insert_with(SurfaceSettings::default).caret = Some((x, y));
+        if state.focus == Some(surface) {
+            state.update();
+        }
+    }
+
+    /// Forgets about a surface that is being destroyed.
+    pub fn forget(&self, surface: *mut wl_proxy) {
+        let mut state = self.state.lock().unwrap();
+        state.surfaces.remove(&(surface as usize));
+        if state.focus == Some(surface) {
+            state.focus = None;
+        }
+    }
+
+    /// Returns the events generated while dispatching, with the surface they are for.
+    pub fn take_events(&self) -> Vec<(GlutinEvent, *mut wl_proxy)> {
+        mem::replace(&mut self.state.lock().unwrap().events, Vec::new())
+    }
+}
+
+impl Drop for TextInput {
+    fn drop(&mut self) {
+        let state = self.state.lock().unwrap();
+        unsafe { protocols::destroy(state.proxy, text_input::request::DESTROY) };
+    }
+}
+
+impl TextInputState {
+    fn settings(&self, surface: *mut wl_proxy) -> SurfaceSettings {
+        self.surfaces.get(&(surface as usize)).cloned().unwrap_or_else(SurfaceSettings::default)
+    }
+
+    /// Enables or disables the text input for the focused surface, and sends its caret.
+    fn update(&mut self) {
+        let settings = match self.focus {
+            Some(surface) => self.settings(surface),
+            None => return
+        };
+        unsafe {
+            if settings.allowed {
+                if !self.enabled {
+                    ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_marshal, self.proxy,
+                                  text_input::request::ENABLE);
+                }
+                if let Some((x, y)) = settings.caret {
+                    ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_marshal, self.proxy,
+                                  text_input::request::SET_CURSOR_RECTANGLE, x, y, 0i32, 0i32);
+                }
+            } else if self.enabled {
+                ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_marshal, self.proxy,
+                              text_input::request::DISABLE);
+                self.end_composition();
+            } else {
+                return;
+            }
+            ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_marshal, self.proxy,
+                          text_input::request::COMMIT);
+        }
+        self.enabled = settings.allowed;
+    }
+
+    /// Tells the focused surface that the composition ended, if there was one.
+    fn end_composition(&mut self) {
+        if let (true, Some(surface)) = (self.composing, self.focus) {
+            self.events.push((GlutinEvent::ImePreedit(String::new(), None), surface));
+        }
+        self.composing = false;
+    }
+
+    fn event(&mut self, opcode: u32, args: *const wl_argument) {
+        unsafe {
+            match opcode {
+                text_input::event::ENTER => {
+                    self.focus = Some(protocols::arg_object(args, 0));
+                    self.update();
+                },
+                text_input::event::LEAVE => {
+                    // the compositor disables the text input by itself
+                    self.end_composition();
+                    self.focus = None;
+                    self.enabled = false;
+                },
+                text_input::event::PREEDIT_STRING => {
+                    let text = protocols::arg_string(args, 0).unwrap_or_else(String::new);
+                    let (begin, end) = (protocols::arg_int(args, 1), protocols::arg_int(args, 2));
+                    // the cursor is hidden when it has negative offsets
+                    let cursor = if begin < 0 || end < 0 {
+                        None
+                    } else {
+                        Some((begin as usize, end as usize))
+                    };
+                    self.pending_preedit = Some((text, cursor));
+                },
+                text_input::event::COMMIT_STRING => {
+                    self.pending_commit = protocols::arg_string(args, 0);
+                },
+                text_input::event::DONE => self.done(),
+                // we don't send the surrounding text, so the input method can't delete it
+                _ => ()
+            }
+        }
+    }
+
+    /// Applies the changes received since the previous `done` event.
+    fn done(&mut self) {
+        let preedit = match self.pending_preedit.take() {
+            Some((ref text, _)) if text.is_empty() => None,
+            preedit => preedit
+        };
+        let commit = self.pending_commit.take();
+        let surface = match self.focus {
+            Some(surface) => surface,
+            None => return
+        };
+
+        // the preedit text is replaced by the committed text, then by the new preedit text
+        if preedit.is_none() {
+            self.end_composition();
+        }
+        if let Some(text) = commit {
+            self.events.push((GlutinEvent::ImeCommit(text.clone()), surface));
+            for c in text.chars() {
+                self.events.push((GlutinEvent::ReceivedCharacter(c), surface));
+            }
+        }
+        if let Some((text, cursor)) = preedit {
+            self.composing = true;
+            self.events.push((GlutinEvent::ImePreedit(text, cursor), surface));
+        }
+    }
+}
+
+extern "C" fn dispatcher(_: *const c_void, proxy: *mut c_void, opcode: u32,
+                         _: *const wl_message, args: *const wl_argument)
+{
+    unsafe {
+        let state = &*(protocols::user_data(proxy) as *const Mutex<TextInputState>);
+        state.lock().unwrap().event(opcode, args);
+    }
+}
@@ -3,13 +3,14 @@ use std::sync::{Arc, Mutex};
 
 use libc;
 
-use {CreationError, CursorState, Event, MouseCursor, WindowAttributes};
+use {CreationError, CursorState, Event, MouseCursor, VideoMode, WindowAttributes};
 use platform::MonitorId as PlatformMonitorId;
+use platform::PlatformSpecificWindowBuilderAttributes;
 
 use wayland_client::EventIterator;
 use wayland_client::wayland::compositor::WlSurface;
 use wayland_client::wayland::shell::WlShellSurface;
-use super::wayland_window::{DecoratedSurface, add_borders, substract_borders};
+use super::decorations::{DecoratedSurface, add_borders, substract_borders};
 use super::context::{WaylandContext, WAYLAND_CONTEXT};
 
 #[derive(Clone)]
@@ -22,12 +23,99 @@ impl WindowProxy {
     }
 }
 
+/// Size constraints, which the compositor doesn't know about with `wl_shell` so we apply them
+/// to the sizes it suggests.
+// TODO: send them with `xdg_toplevel.set_min_size`/`set_max_size` once we use xdg-shell
+struct SizeLimits {
+    min_dimensions: Option<(u32, u32)>,
+    max_dimensions: Option<(u32, u32)>,
+    resizable: bool,
+    /// Size the window is kept at when it isn't resizable
+    size: (u32, u32),
+    resize_increments: Option<(u32, u32)>,
+    aspect_ratio: Option<(u32, u32)>,
+}
+
+impl SizeLimits {
+    /// Returns the size closest to the suggested one that satisfies the constraints.
+    ///
+    /// The increments and the minimum and maximum sizes are applied first, then the window is
+    /// shrunk to the aspect ratio so that it stays within those bounds.
+    fn clamp(&self, w: i32, h: i32) -> (i32, i32) {
+        if !self.resizable {
+            return (self.size.0 as i32, self.size.1 as i32);
+        }
+
+        let (mut w, mut h) = self.clamp_to_limits(w, h);
+        if let Some((ratio_w, ratio_h)) = self.aspect_ratio {
+            if ratio_w != 0 && ratio_h != 0 {
+                let (ratio_w, ratio_h) = (ratio_w as i32, ratio_h as i32);
+                if w * ratio_h > h * ratio_w {
+                    w = h * ratio_w / ratio_h;
+                } else {
+                    h = w * ratio_h / ratio_w;
+                }
+
+                // shrinking went below the minimum size, use the smallest size with the ratio
+                if let Some((min_w, min_h)) = self.min_dimensions {
+                    let (min_w, min_h) = (min_w as i32, min_h as i32);
+                    if w < min_w || h < min_h {
+                        if min_w * ratio_h > min_h * ratio_w {
+                            w = min_w;
+                            h = (min_w * ratio_h + ratio_w - 1) / ratio_w;
+                        } else {
+                            w = (min_h * ratio_w + ratio_h - 1) / ratio_h;
+                            h = min_h;
+                        }
+                    }
+                }
+                // the limits win if they don't leave room for the ratio
+                if let Some((max_w, max_h)) = self.max_dimensions {
+                    w = ::std::cmp::min(w, max_w as i32);
+                    h = ::std::cmp::min(h, max_h as i32);
+                }
+            }
+        }
+        (w, h)
+    }
+
+    /// Applies the increments, then the minimum and maximum sizes.
+    fn clamp_to_limits(&self, w: i32, h: i32) -> (i32, i32) {
+        let (mut w, mut h) = (w, h);
+        if let Some((inc_w, inc_h)) = self.resize_increments {
+            // the steps start from the minimum size, like on X11
+            let (base_w, base_h) = self.min_dimensions.unwrap_or((0, 0));
+            if inc_w != 0 && w > base_w as i32 {
+                w -= (w - base_w as i32) % inc_w as i32;
+            }
+            if inc_h != 0 && h > base_h as i32 {
+                h -= (h - base_h as i32) % inc_h as i32;
+            }
+        }
+        if let Some((max_w, max_h)) = self.max_dimensions {
+            w = ::std::cmp::min(w, max_w as i32);
+            h = ::std::cmp::min(h, max_h as i32);
+        }
+        if let Some((min_w, min_h)) = self.min_dimensions {
+            w = ::std::cmp::max(w, min_w as i32);
+            h = ::std::cmp::max(h, min_h as i32);
+        }
+        (w, h)
+    }
+}
+
 pub struct Window {
     wayland_context: &'static WaylandContext,
     surface: WlSurface,
     shell_window: Mutex<ShellWindow>,
     evt_queue: Arc<Mutex<VecDeque<Event>>>,
     inner_size: Mutex<(i32, i32)>,
+    size_limits: Mutex<SizeLimits>,
+    /// Size to restore when leaving fullscreen or the maximized state
+    windowed_size: Mutex<Option<(i32, i32)>>,
+    maximized: Mutex<bool>,
+    /// Whether the window is waiting for the configure that maximizes it
+    maximize_pending: Mutex<bool>,
     resize_callback: Option<fn(u32, u32)>,
 }
 
@@ -65,7 +153,16 @@ impl Window {
         }
 
         if let Some((w, h)) = newsize {
-            let (w, h) = substract_borders(w, h);
+            let (w, h) = match *shell_window_guard {
+                ShellWindow::Decorated(ref deco) if deco.is_decorated() => substract_borders(w, h),
+                _ => (w, h)
+            };
+            // the compositor decides of the size of a fullscreen or maximized window
+            let (w, h) = if self.windowed_size.lock().unwrap().is_some() {
+                (w, h)
+            } else {
+                self.size_limits.lock().unwrap().clamp(w, h)
+            };
             *self.inner_size.lock().unwrap() = (w, h);
             if let ShellWindow::Decorated(ref mut deco) = *shell_window_guard {
                 deco.resize(w, h);
@@ -73,6 +170,12 @@ impl Window {
             if let Some(f) = self.resize_callback {
                 f(w as u32, h as u32);
             }
+            // `wl_shell` has no maximized state, the configure is all we get
+            let mut maximize_pending = self.maximize_pending.lock().unwrap();
+            if *maximize_pending {
+                *maximize_pending = false;
+                evt_queue_guard.push_back(Event::Maximized(true));
+            }
             Some(Event::Resized(w as u32, h as u32))
         } else {
             evt_queue_guard.pop_front()
@@ -130,19 +233,26 @@ enum ShellWindow {
 }
 
 impl Window {
-    pub fn new(window: &WindowAttributes) -> Result<Window, CreationError>
+    pub fn new(window: &WindowAttributes, pl_attribs: &PlatformSpecificWindowBuilderAttributes)
+               -> Result<Window, CreationError>
     {
         use wayland_client::Proxy;
-        // not implemented
-        assert!(window.min_dimensions.is_none());
-        assert!(window.max_dimensions.is_none());
 
         let wayland_context = match *WAYLAND_CONTEXT {
             Some(ref c) => c,
             None => return Err(CreationError::NotSupported),
         };
 
+        let size_limits = SizeLimits {
+            min_dimensions: window.min_dimensions,
+            max_dimensions: window.max_dimensions,
+            resizable: window.resizable,
+            size: window.dimensions.unwrap_or((800, 600)),
+            resize_increments: window.resize_increments,
+            aspect_ratio: window.aspect_ratio,
+        };
         let (w, h) = window.dimensions.unwrap_or((800, 600));
+        let (w, h) = size_limits.clamp(w as i32, h as i32);
 
         let (surface, evt_queue) = match wayland_context.new_surface() {
             Some(t) => t,
@@ -151,7 +261,7 @@ impl Window {
 
         let shell_window = if let Some(PlatformMonitorId::Wayland(ref monitor_id)) = window.monitor {
             let pid = super::monitor::proxid_from_monitorid(monitor_id);
-            match wayland_context.plain_from(&surface, Some(pid)) {
+            match wayland_context.plain_from(&surface, Some(pid), window.video_mode) {
                 Some(mut s) => {
                     let iter = EventIterator::new();
                     s.set_evt_iterator(&iter);
@@ -160,12 +270,12 @@ impl Window {
                 None => return Err(CreationError::NotSupported)
             }
         } else if window.decorations {
-            match wayland_context.decorated_from(&surface, w as i32, h as i32) {
+            match wayland_context.decorated_from(&surface, w, h) {
                 Some(s) => ShellWindow::Decorated(s),
                 None => return Err(CreationError::NotSupported)
             }
         } else {
-            match wayland_context.plain_from(&surface, None) {
+            match wayland_context.plain_from(&surface, None, None) {
                 Some(mut s) => {
                     let iter = EventIterator::new();
                     s.set_evt_iterator(&iter);
@@ -175,12 +285,23 @@ impl Window {
             }
         };
 
+        if let Some((_, ref class)) = pl_attribs.class {
+            match shell_window {
+                ShellWindow::Plain(ref plain, _) => { plain.set_class(class.clone()); },
+                ShellWindow::Decorated(ref deco) => { deco.get_shell().set_class(class.clone()); }
+            }
+        }
+
         Ok(Window {
             wayland_context: wayland_context,
             surface: surface,
             shell_window: Mutex::new(shell_window),
             evt_queue: evt_queue,
-            inner_size: Mutex::new((w as i32, h as i32)),
+            inner_size: Mutex::new((w, h)),
+            size_limits: Mutex::new(size_limits),
+            windowed_size: Mutex::new(None),
+            maximized: Mutex::new(false),
+            maximize_pending: Mutex::new(false),
             resize_callback: None,
         })
     }
@@ -189,8 +310,118 @@ impl Window {
         let guard = self.shell_window.lock().unwrap();
         match *guard {
             ShellWindow::Plain(ref plain, _) => { plain.set_title(title.into()); },
-            ShellWindow::Decorated(ref deco) => { deco.set_title(title.into()); }
+            ShellWindow::Decorated(ref deco) => { deco.get_shell().set_title(title.into()); }
+        }
+    }
+
+    pub fn set_fullscreen(&self, monitor: Option<PlatformMonitorId>, video_mode: Option<VideoMode>) {
+        match monitor {
+            Some(monitor) => {
+                self.save_windowed_size();
+                let monitor_id = match monitor {
+                    PlatformMonitorId::Wayland(ref monitor_id) => Some(super::monitor::proxid_from_monitorid(monitor_id)),
+                    _ => None
+                };
+                // the borders would end up outside of the monitor
+                match *self.shell_window.lock().unwrap() {
+                    ShellWindow::Plain(ref plain, _) => {
+                        self.wayland_context.set_fullscreen(plain, monitor_id, video_mode);
+                    },
+                    ShellWindow::Decorated(ref mut deco) => {
+                        deco.set_decorate(false);
+                        self.wayland_context.set_fullscreen(deco.get_shell(), monitor_id, video_mode);
+                    }
+                }
+            },
+            None => {
+                match *self.shell_window.lock().unwrap() {
+                    ShellWindow::Plain(ref plain, _) => plain.set_toplevel(),
+                    ShellWindow::Decorated(ref mut deco) => {
+                        deco.get_shell().set_toplevel();
+                        deco.set_decorate(true);
+                    }
+                }
+                self.restore_windowed_size();
+            }
+        }
+    }
+
+    pub fn set_maximized(&self, maximized: bool) {
+        {
+            let mut is_maximized = self.maximized.lock().unwrap();
+            if *is_maximized == maximized {
+                return;
+            }
+            *is_maximized = maximized;
+        }
+
+        *self.maximize_pending.lock().unwrap() = maximized;
+        if maximized {
+            self.save_windowed_size();
+            // without an output the compositor picks the one the window is on
+            match *self.shell_window.lock().unwrap() {
+                ShellWindow::Plain(ref plain, _) => plain.set_maximized(None),
+                ShellWindow::Decorated(ref deco) => deco.get_shell().set_maximized(None)
+            }
+        } else {
+            match *self.shell_window.lock().unwrap() {
+                ShellWindow::Plain(ref plain, _) => plain.set_toplevel(),
+                ShellWindow::Decorated(ref deco) => deco.get_shell().set_toplevel()
+            }
+            self.restore_windowed_size();
+            self.evt_queue.lock().unwrap().push_back(Event::Maximized(false));
+        }
+    }
+
+    #[inline]
+    pub fn set_minimized(&self, _minimized: bool) {
+        // TODO: `wl_shell` can't minimize, requires `xdg_toplevel.set_minimized`
+    }
+
+    #[inline]
+    pub fn restore(&self) {
+        self.set_maximized(false);
+    }
+
+    /// Remembers the size of the window before it gets maximized or fullscreen.
+    fn save_windowed_size(&self) {
+        let mut windowed_size = self.windowed_size.lock().unwrap();
+        if windowed_size.is_none() {
+            *windowed_size = Some(*self.inner_size.lock().unwrap());
+        }
+    }
+
+    /// Gives back its size to a window that is back to being a normal toplevel, since with
+    /// `wl_shell` the compositor doesn't suggest a size for them.
+    fn restore_windowed_size(&self) {
+        let windowed_size = self.windowed_size.lock().unwrap().take();
+        if let Some((w, h)) = windowed_size {
+            self.resize(w, h);
+        }
+    }
+
+    /// Resizes the window on our side, and reports it.
+    fn resize(&self, w: i32, h: i32) {
+        *self.inner_size.lock().unwrap() = (w, h);
+        if let ShellWindow::Decorated(ref mut deco) = *self.shell_window.lock().unwrap() {
+            deco.resize(w, h);
+        }
+        if let Some(f) = self.resize_callback {
+            f(w as u32, h as u32);
         }
+        self.evt_queue.lock().unwrap().push_back(Event::Resized(w as u32, h as u32));
+    }
+
+    #[inline]
+    pub fn set_decorations(&self, _decorations: bool) {
+        // TODO: the decorations are a parent surface of ours, switching requires recreating
+        //       the shell surface and the window state that goes with it
+    }
+
+    #[inline]
+    pub fn set_window_icon(&self, _icon: Option<&::Icon>) {
+        // the core protocol has no icons, the compositor takes them from the desktop file
+        // matching the class of the shell surface
     }
 
     #[inline]
@@ -222,12 +453,16 @@ impl Window {
     #[inline]
     pub fn get_outer_size(&self) -> Option<(u32, u32)> {
         let (w, h) = *self.inner_size.lock().unwrap();
-        let (w, h) = add_borders(w, h);
+        let (w, h) = match *self.shell_window.lock().unwrap() {
+            ShellWindow::Decorated(ref deco) if deco.is_decorated() => add_borders(w, h),
+            _ => (w, h)
+        };
         Some((w as u32, h as u32))
     }
 
     #[inline]
     pub fn set_inner_size(&self, x: u32, y: u32) {
+        self.size_limits.lock().unwrap().size = (x, y);
         let mut guard = self.shell_window.lock().unwrap();
         match *guard {
             ShellWindow::Decorated(ref mut deco) => { deco.resize(x as i32, y as i32); },
@@ -235,6 +470,45 @@ impl Window {
         }
     }
 
+    #[inline]
+    pub fn set_min_dimensions(&self, dimensions: Option<(u32, u32)>) {
+        self.size_limits.lock().unwrap().min_dimensions = dimensions;
+        self.apply_size_limits();
+    }
+
+    #[inline]
+    pub fn set_max_dimensions(&self, dimensions: Option<(u32, u32)>) {
+        self.size_limits.lock().unwrap().max_dimensions = dimensions;
+        self.apply_size_limits();
+    }
+
+    /// Resizes the window if its current size doesn't fit the limits anymore.
+    fn apply_size_limits(&self) {
+        // the shell window must not be locked with the size, `next_event` locks them the other
+        // way around
+        if self.windowed_size.lock().unwrap().is_some() {
+            // they apply again once the window is back to its windowed size
+            return;
+        }
+        let (w, h) = {
+            let mut inner_size = self.inner_size.lock().unwrap();
+            let size = self.size_limits.lock().unwrap().clamp(inner_size.0, inner_size.1);
+            if size == *inner_size {
+                return;
+            }
+            *inner_size = size;
+            size
+        };
+
+        if let ShellWindow::Decorated(ref mut deco) = *self.shell_window.lock().unwrap() {
+            deco.resize(w, h);
+        }
+        if let Some(f) = self.resize_callback {
+            f(w as u32, h as u32);
+        }
+        self.evt_queue.lock().unwrap().push_back(Event::Resized(w as u32, h as u32));
+    }
+
     #[inline]
     pub fn create_window_proxy(&self) -> WindowProxy {
         WindowProxy
@@ -286,6 +560,41 @@ impl Window {
         Err(())
     }
 
+    #[inline]
+    pub fn get_clipboard(&self, mime_type: &str) -> Option<Vec<u8>> {
+        self.wayland_context.get_clipboard(mime_type)
+    }
+
+    #[inline]
+    pub fn set_clipboard(&self, mime_type: &str, data: Vec<u8>) {
+        self.wayland_context.set_clipboard(mime_type, data)
+    }
+
+    #[inline]
+    pub fn start_drag(&self, contents: Vec<(String, Vec<u8>)>) {
+        self.wayland_context.start_drag(&self.surface, contents)
+    }
+
+    #[inline]
+    pub fn get_primary_selection(&self, mime_type: &str) -> Option<Vec<u8>> {
+        self.wayland_context.get_primary_selection(mime_type)
+    }
+
+    #[inline]
+    pub fn set_primary_selection(&self, mime_type: &str, data: Vec<u8>) {
+        self.wayland_context.set_primary_selection(mime_type, data)
+    }
+
+    #[inline]
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        self.wayland_context.set_ime_allowed(&self.surface, allowed)
+    }
+
+    #[inline]
+    pub fn set_ime_position(&self, x: i32, y: i32) {
+        self.wayland_context.set_ime_position(&self.surface, x, y)
+    }
+
     #[inline]
     pub fn get_wayland_display(&self) -> *mut libc::c_void {
         WAYLAND_CONTEXT.as_ref().unwrap() // context exists if window was created
@@ -313,7 +622,76 @@ impl Window {
 
 impl Drop for Window {
     fn drop(&mut self) {
-        use wayland_client::Proxy;
-        self.wayland_context.dropped_surface(self.surface.id());
+        self.wayland_context.dropped_surface(&self.surface);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SizeLimits;
+
+    fn limits() -> SizeLimits {
+        SizeLimits {
+            min_dimensions: None,
+            max_dimensions: None,
+            resizable: true,
+            size: (800, 600),
+            resize_increments: None,
+            aspect_ratio: None,
+        }
+    }
+
+    #[test]
+    fn size_limits_without_limits() {
+        assert_eq!(limits().clamp(1024, 768), (1024, 768));
+    }
+
+    #[test]
+    fn size_limits_min_max() {
+        let size_limits = SizeLimits { min_dimensions: Some((200, 100)), max_dimensions: Some((1000, 500)),
+                                       ..limits() };
+        assert_eq!(size_limits.clamp(100, 50), (200, 100));
+        assert_eq!(size_limits.clamp(1200, 800), (1000, 500));
+        assert_eq!(size_limits.clamp(640, 480), (640, 480));
+    }
+
+    #[test]
+    fn size_limits_not_resizable() {
+        let size_limits = SizeLimits { resizable: false, min_dimensions: Some((200, 100)), ..limits() };
+        assert_eq!(size_limits.clamp(100, 50), (800, 600));
+        assert_eq!(size_limits.clamp(1024, 768), (800, 600));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn size_limits_increments() {
+        // the steps start from the minimum size
+        let size_limits = SizeLimits { resize_increments: Some((8, 16)), min_dimensions: Some((200, 100)),
+                                       ..limits() };
+        assert_eq!(size_limits.clamp(211, 133), (208, 132));
+        assert_eq!(size_limits.clamp(150, 50), (200, 100));
+    }
+
+    #[test]
+    fn size_limits_aspect_ratio_within_limits() {
+        let size_limits = SizeLimits { aspect_ratio: Some((16, 9)), ..limits() };
+        assert_eq!(size_limits.clamp(1600, 1000), (1600, 900));
+        assert_eq!(size_limits.clamp(1700, 900), (1600, 900));
+
+        // the ratio is kept while staying below the maximum size
+        let size_limits = SizeLimits { aspect_ratio: Some((16, 9)), max_dimensions: Some((1000, 500)),
+                                       ..limits() };
+        assert_eq!(size_limits.clamp(1200, 800), (888, 500));
+
+        // and above the minimum size
+        let size_limits = SizeLimits { aspect_ratio: Some((1, 1)), min_dimensions: Some((300, 200)),
+                                       ..limits() };
+        assert_eq!(size_limits.clamp(400, 100), (300, 300));
+    }
+
+    #[test]
+    fn size_limits_increments_before_aspect_ratio() {
+        let size_limits = SizeLimits { resize_increments: Some((10, 10)), aspect_ratio: Some((2, 1)),
+                                       ..limits() };
+        assert_eq!(size_limits.clamp(205, 205), (200, 100));
+    }
+}
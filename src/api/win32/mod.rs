@@ -337,6 +337,71 @@ impl Window {
 
         Ok(())
     }
+
+    #[inline]
+    pub fn get_clipboard(&self, _mime_type: &str) -> Option<Vec<u8>> {
+        None
+    }
+
+    #[inline]
+    pub fn set_clipboard(&self, _mime_type: &str, _data: Vec<u8>) {
+    }
+
+    #[inline]
+    pub fn get_primary_selection(&self, _mime_type: &str) -> Option<Vec<u8>> {
+        None
+    }
+
+    #[inline]
+    pub fn set_primary_selection(&self, _mime_type: &str, _data: Vec<u8>) {
+    }
+
+    #[inline]
+    pub fn start_drag(&self, _contents: Vec<(String, Vec<u8>)>) {
+    }
+
+    #[inline]
+    pub fn set_min_dimensions(&self, dimensions: Option<(u32, u32)>) {
+        // read by the callback on the next `WM_GETMINMAXINFO`
+        self.window_state.lock().unwrap().attributes.min_dimensions = dimensions;
+    }
+
+    #[inline]
+    pub fn set_max_dimensions(&self, dimensions: Option<(u32, u32)>) {
+        self.window_state.lock().unwrap().attributes.max_dimensions = dimensions;
+    }
+
+    #[inline]
+    pub fn set_fullscreen(&self, _monitor: Option<MonitorId>, _video_mode: Option<::VideoMode>) {
+    }
+
+    #[inline]
+    pub fn set_maximized(&self, _maximized: bool) {
+    }
+
+    #[inline]
+    pub fn set_minimized(&self, _minimized: bool) {
+    }
+
+    #[inline]
+    pub fn restore(&self) {
+    }
+
+    #[inline]
+    pub fn set_decorations(&self, _decorations: bool) {
+    }
+
+    #[inline]
+    pub fn set_window_icon(&self, _icon: Option<&::Icon>) {
+    }
+
+    #[inline]
+    pub fn set_ime_allowed(&self, _allowed: bool) {
+    }
+
+    #[inline]
+    pub fn set_ime_position(&self, _x: i32, _y: i32) {
+    }
 }
 
 pub struct PollEventsIterator<'a> {
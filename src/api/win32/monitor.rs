@@ -169,6 +169,11 @@ impl MonitorId {
         self.dimensions
     }
 
+    #[inline]
+    pub fn get_video_modes(&self) -> Vec<::VideoMode> {
+        Vec::new()
+    }
+
     /// This is a Win32-only function for `MonitorId` that returns the system name of the adapter
     /// device.
     #[inline]
@@ -0,0 +1,651 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::{cmp, mem, ptr, slice};
+
+use libc;
+
+use super::ffi;
+use super::XConnection;
+
+/// MIME type used for text by the public API.
+pub const TEXT_MIME_TYPE: &'static str = "text/plain;charset=utf-8";
+
+/// Time to wait for the owner of a selection to answer a request, in milliseconds. Reading a
+/// selection takes two requests, one for the available targets and one for the data.
+const SELECTION_TIMEOUT: u64 = 1000;
+
+/// Data larger than this is sent with the INCR mechanism, in chunks of this size.
+const INCR_CHUNK_SIZE: usize = 64 * 1024;
+
+lazy_static! {
+    /// Selections owned by the windows of the process. They are shared so that reading the
+    /// selection of another of our windows doesn't wait for an answer from an event loop that
+    /// may be the one waiting.
+    static ref OWNED_SELECTIONS: Mutex<Vec<OwnedSelection>> = Mutex::new(Vec::new());
+}
+
+struct Atoms {
+    clipboard: ffi::Atom,
+    primary: ffi::Atom,
+    targets: ffi::Atom,
+    timestamp: ffi::Atom,
+    multiple: ffi::Atom,
+    atom_pair: ffi::Atom,
+    incr: ffi::Atom,
+    utf8_string: ffi::Atom,
+    /// Property of our window in which the owners of selections write the data we ask for
+    transfer: ffi::Atom,
+    /// Property of our window that is changed to get the current server time
+    time_query: ffi::Atom,
+}
+
+/// Contents of a selection that one of our windows owns, in the different targets we can
+/// provide.
+struct OwnedSelection {
+    window: ffi::Window,
+    selection: ffi::Atom,
+    /// Time at which the window became the owner
+    time: ffi::Time,
+    contents: Vec<(ffi::Atom, Arc<Vec<u8>>)>,
+}
+
+/// A transfer to another client that is being done in chunks.
+struct IncrTransfer {
+    requestor: ffi::Window,
+    property: ffi::Atom,
+    target: ffi::Atom,
+    data: Arc<Vec<u8>>,
+    offset: usize,
+}
+
+/// The X11 event that `wait_for_event` is waiting for.
+struct EventFilter {
+    window: ffi::Window,
+    event_type: libc::c_int,
+    atom: ffi::Atom,
+    /// For `PropertyNotify`, the state of the property
+    state: libc::c_int,
+}
+
+/// Implements the ICCCM selection protocol for a window.
+///
+/// Requests from other clients are served from the event loop through `handle_event`, while
+/// reading a selection waits for the answer of its owner without dispatching anything else.
+pub struct Clipboard {
+    display: Arc<XConnection>,
+    window: ffi::Window,
+    atoms: Atoms,
+    transfers: Mutex<Vec<IncrTransfer>>,
+    /// Time of the latest input event, which the ICCCM requires for the selection requests
+    time: Mutex<ffi::Time>,
+}
+
+impl Clipboard {
+    pub fn new(display: &Arc<XConnection>, window: ffi::Window) -> Clipboard {
+        let atoms = Atoms {
+            clipboard: display.get_atom("CLIPBOARD"),
+            primary: ffi::XA_PRIMARY,
+            targets: display.get_atom("TARGETS"),
+            timestamp: display.get_atom("TIMESTAMP"),
+            multiple: display.get_atom("MULTIPLE"),
+            atom_pair: display.get_atom("ATOM_PAIR"),
+            incr: display.get_atom("INCR"),
+            utf8_string: display.get_atom("UTF8_STRING"),
+            transfer: display.get_atom("_WINIT_SELECTION"),
+            time_query: display.get_atom("_WINIT_TIME_QUERY"),
+        };
+        display.check_errors().expect("Failed to call XInternAtom");
+
+        Clipboard {
+            display: display.clone(),
+            window: window,
+            atoms: atoms,
+            transfers: Mutex::new(Vec::new()),
+            time: Mutex::new(ffi::CurrentTime),
+        }
+    }
+
+    /// Records the time of an input event, which is used as the time of the next selection
+    /// requests.
+    pub fn update_time(&self, time: ffi::Time) {
+        *self.time.lock().unwrap() = time;
+    }
+
+    /// Returns the contents of the clipboard in the given MIME type.
+    pub fn get(&self, mime_type: &str) -> Option<Vec<u8>> {
+        let selection = self.atoms.clipboard;
+        self.get_selection(selection, mime_type)
+    }
+
+    /// Replaces the contents of the clipboard.
+    pub fn set(&self, mime_type: &str, data: Vec<u8>) {
+        let selection = self.atoms.clipboard;
+        self.set_selection(selection, mime_type, data)
+    }
+
+    /// Returns the contents of the primary selection in the given MIME type.
+    pub fn get_primary(&self, mime_type: &str) -> Option<Vec<u8>> {
+        let selection = self.atoms.primary;
+        self.get_selection(selection, mime_type)
+    }
+
+    /// Replaces the contents of the primary selection.
+    pub fn set_primary(&self, mime_type: &str, data: Vec<u8>) {
+        let selection = self.atoms.primary;
+        self.set_selection(selection, mime_type, data)
+    }
+
+    /// Processes the selection-related events of the event loop.
+    pub fn handle_event(&self, xev: &ffi::XEvent) {
+        match xev.get_type() {
+            ffi::SelectionRequest => {
+                let request: &ffi::XSelectionRequestEvent = unsafe { mem::transmute(xev) };
+                self.answer_request(request);
+            },
+
+            ffi::SelectionClear => {
+                let clear: &ffi::XSelectionClearEvent = unsafe { mem::transmute(xev) };
+                OWNED_SELECTIONS.lock().unwrap().retain(|owned| {
+                    owned.window != clear.window || owned.selection != clear.selection
+                });
+            },
+
+            ffi::PropertyNotify => {
+                let property: &ffi::XPropertyEvent = unsafe { mem::transmute(xev) };
+                if property.state == ffi::PropertyDelete {
+                    self.continue_transfer(property.window, property.atom);
+                }
+            },
+
+            _ => ()
+        }
+    }
+
+    /// Returns the contents of any selection in the given MIME type.
+    pub fn get_selection(&self, selection: ffi::Atom, mime_type: &str) -> Option<Vec<u8>> {
+        let targets = self.targets_for(mime_type);
+
+        let owner = unsafe { (self.display.xlib.XGetSelectionOwner)(self.display.display, selection) };
+        if owner == 0 {
+            return None;
+        }
+
+        // if one of our windows owns the selection, asking the server could deadlock since the
+        // answer may have to come from the event loop that is waiting for it
+        {
+            let owned = OWNED_SELECTIONS.lock().unwrap();
+            if let Some(owned) = owned.iter().find(|o| o.window == owner && o.selection == selection) {
+                return targets.iter()
+                              .filter_map(|target| owned.contents.iter().find(|c| c.0 == *target))
+                              .map(|c| (*c.1).clone())
+                              .next();
+            }
+        }
+
+        // only one target is converted, the first that the owner lists, so that an owner that
+        // doesn't answer only makes us wait for two requests
+        let time = self.event_time();
+        let available = match self.convert_selection(selection, self.atoms.targets, time) {
+            Some(data) => atoms_from_bytes(&data),
+            None => return None
+        };
+        targets.into_iter()
+               .find(|target| available.contains(target))
+               .and_then(|target| self.convert_selection(selection, target, time))
+    }
+
+    fn set_selection(&self, selection: ffi::Atom, mime_type: &str, data: Vec<u8>) {
+        self.set_selection_contents(selection, vec![(mime_type.to_owned(), data)]);
+    }
+
+    /// Takes ownership of a selection with data in several MIME types, and returns the targets
+    /// it can be converted to, or nothing if another client kept the ownership.
+    pub fn set_selection_contents(&self, selection: ffi::Atom, contents: Vec<(String, Vec<u8>)>)
+                                  -> Vec<ffi::Atom>
+    {
+        let mut targets = Vec::new();
+        let mut owned_contents = Vec::new();
+        for (mime_type, data) in contents {
+            let data = Arc::new(data);
+            for target in self.targets_for(&mime_type) {
+                if !targets.contains(&target) {
+                    targets.push(target);
+                    owned_contents.push((target, data.clone()));
+                }
+            }
+        }
+        let contents = owned_contents;
+        let time = self.event_time();
+
+        // the contents must be known before the requests can arrive
+        {
+            let mut owned = OWNED_SELECTIONS.lock().unwrap();
+            owned.retain(|owned| owned.selection != selection);
+            owned.push(OwnedSelection {
+                window: self.window,
+                selection: selection,
+                time: time,
+                contents: contents,
+            });
+        }
+
+        let owner = unsafe {
+            (self.display.xlib.XSetSelectionOwner)(self.display.display, selection, self.window, time);
+            (self.display.xlib.XGetSelectionOwner)(self.display.display, selection)
+        };
+        self.display.check_errors().expect("Failed to call XSetSelectionOwner");
+
+        if owner != self.window {
+            OWNED_SELECTIONS.lock().unwrap().retain(|owned| {
+                owned.window != self.window || owned.selection != selection
+            });
+            return Vec::new();
+        }
+
+        targets
+    }
+
+    /// Returns the time of the latest input event, or the current server time if there was
+    /// none yet.
+    fn event_time(&self) -> ffi::Time {
+        let time = *self.time.lock().unwrap();
+        if time != ffi::CurrentTime {
+            return time;
+        }
+
+        // appending nothing to a property generates a notification that holds the server time
+        unsafe {
+            (self.display.xlib.XChangeProperty)(self.display.display, self.window, self.atoms.time_query,
+                                                ffi::XA_INTEGER, 32, ffi::PropModeAppend, ptr::null(), 0);
+            (self.display.xlib.XFlush)(self.display.display);
+        }
+        self.display.check_errors().expect("Failed to call XChangeProperty");
+
+        match self.wait_for_event(EventFilter {
+            window: self.window,
+            event_type: ffi::PropertyNotify,
+            atom: self.atoms.time_query,
+            state: ffi::PropertyNewValue,
+        }) {
+            Some(xev) => {
+                let property: &ffi::XPropertyEvent = unsafe { mem::transmute(&xev) };
+                property.time
+            },
+            None => ffi::CurrentTime
+        }
+    }
+
+    /// Returns the targets that correspond to a MIME type, by order of preference.
+    fn targets_for(&self, mime_type: &str) -> Vec<ffi::Atom> {
+        let target = self.display.get_atom(mime_type);
+        if mime_type == TEXT_MIME_TYPE {
+            vec![self.atoms.utf8_string, target]
+        } else {
+            vec![target]
+        }
+    }
+
+    /// Asks the owner of a selection for its contents and waits for them.
+    fn convert_selection(&self, selection: ffi::Atom, target: ffi::Atom, time: ffi::Time) -> Option<Vec<u8>> {
+        let xlib = &self.display.xlib;
+
+        unsafe {
+            // discard the answers to previous requests that timed out, those for other selections
+            // are handled with the other events
+            let stale = EventFilter {
+                window: self.window,
+                event_type: ffi::SelectionNotify,
+                atom: selection,
+                state: 0,
+            };
+            let mut xev = mem::uninitialized();
+            while (xlib.XCheckIfEvent)(self.display.display, &mut xev, Some(event_matches),
+                                       &stale as *const EventFilter as ffi::XPointer) != 0 {}
+
+            (xlib.XDeleteProperty)(self.display.display, self.window, self.atoms.transfer);
+            (xlib.XConvertSelection)(self.display.display, selection, target, self.atoms.transfer,
+                                     self.window, time);
+            (xlib.XFlush)(self.display.display);
+        }
+        self.display.check_errors().expect("Failed to call XConvertSelection");
+
+        let notify = match self.wait_for_event(EventFilter {
+            window: self.window,
+            event_type: ffi::SelectionNotify,
+            atom: selection,
+            state: 0,
+        }) {
+            Some(notify) => notify,
+            None => return None
+        };
+
+        // the owner couldn't convert the selection to this target
+        let notify: &ffi::XSelectionEvent = unsafe { mem::transmute(&notify) };
+        if notify.property == 0 {
+            return None;
+        }
+
+        match self.read_property(self.atoms.transfer) {
+            Some((property_type, _)) if property_type == self.atoms.incr => self.receive_incr(),
+            Some((_, data)) => Some(data),
+            None => None
+        }
+    }
+
+    /// Receives data that the owner of a selection sends in chunks.
+    fn receive_incr(&self) -> Option<Vec<u8>> {
+        let mut data = Vec::new();
+
+        loop {
+            if self.wait_for_event(EventFilter {
+                window: self.window,
+                event_type: ffi::PropertyNotify,
+                atom: self.atoms.transfer,
+                state: ffi::PropertyNewValue,
+            }).is_none() {
+                return None;
+            }
+
+            // notifications from before the transfer started don't correspond to a chunk
+            match self.read_property(self.atoms.transfer) {
+                Some((_, ref chunk)) if chunk.is_empty() => return Some(data),
+                Some((_, chunk)) => data.extend(chunk),
+                None => ()
+            }
+        }
+    }
+
+    /// Reads and deletes a property of our window, returning its type and contents.
+    pub fn read_property(&self, property: ffi::Atom) -> Option<(ffi::Atom, Vec<u8>)> {
+        let mut property_type = 0;
+        let mut format = 0;
+        let mut items = 0;
+        let mut bytes_after = 0;
+        let mut data: *mut libc::c_uchar = ptr::null_mut();
+
+        let status = unsafe {
+            (self.display.xlib.XGetWindowProperty)(self.display.display, self.window, property,
+                                                   0, libc::c_long::max_value() / 4, ffi::True,
+                                                   ffi::AnyPropertyType as ffi::Atom,
+                                                   &mut property_type, &mut format, &mut items,
+                                                   &mut bytes_after, &mut data)
+        };
+        self.display.check_errors().expect("Failed to call XGetWindowProperty");
+
+        if status != ffi::Success as libc::c_int || data.is_null() {
+            return None;
+        }
+
+        // items of format 32 are stored as longs by xlib
+        let item_size = match format {
+            16 => mem::size_of::<libc::c_short>(),
+            32 => mem::size_of::<libc::c_long>(),
+            _ => 1,
+        };
+        let contents = unsafe { slice::from_raw_parts(data, items as usize * item_size).to_vec() };
+        unsafe { (self.display.xlib.XFree)(data as *mut _) };
+
+        if property_type == 0 {
+            None
+        } else {
+            Some((property_type, contents))
+        }
+    }
+
+    /// Waits for an event that matches `filter`, leaving the other events in the queue.
+    fn wait_for_event(&self, filter: EventFilter) -> Option<ffi::XEvent> {
+        let start = Instant::now();
+
+        loop {
+            let mut xev = unsafe { mem::uninitialized() };
+            let found = unsafe {
+                (self.display.xlib.XCheckIfEvent)(self.display.display, &mut xev, Some(event_matches),
+                                                  &filter as *const EventFilter as ffi::XPointer)
+            };
+            if found != 0 {
+                return Some(xev);
+            }
+
+            let timeout = Duration::from_millis(SELECTION_TIMEOUT);
+            let elapsed = start.elapsed();
+            if elapsed > timeout {
+                return None;
+            }
+            // the events that were already received didn't match
+            self.display.wait_for_data(timeout - elapsed);
+        }
+    }
+
+    /// Answers a request for the contents of one of our selections.
+    fn answer_request(&self, request: &ffi::XSelectionRequestEvent) {
+        // obsolete clients don't specify a property
+        let property = if request.property == 0 { request.target } else { request.property };
+        let property = if self.write_selection(request, property) { property } else { 0 };
+
+        let mut notify = ffi::XSelectionEvent {
+            type_: ffi::SelectionNotify,
+            serial: 0,
+            send_event: ffi::True,
+            display: self.display.display,
+            requestor: request.requestor,
+            selection: request.selection,
+            target: request.target,
+            property: property,
+            time: request.time,
+        };
+
+        unsafe {
+            (self.display.xlib.XSendEvent)(self.display.display, request.requestor, ffi::False, 0,
+                                           &mut notify as *mut ffi::XSelectionEvent as *mut ffi::XEvent);
+            (self.display.xlib.XFlush)(self.display.display);
+        }
+
+        // the requestor may have been destroyed in the meantime
+        self.display.ignore_error();
+    }
+
+    /// Writes the requested target in the property of the requestor, returns false if the
+    /// target isn't available.
+    fn write_selection(&self, request: &ffi::XSelectionRequestEvent, property: ffi::Atom) -> bool {
+        let owned = OWNED_SELECTIONS.lock().unwrap();
+        let owned = match owned.iter().find(|o| o.window == request.owner && o.selection == request.selection) {
+            Some(owned) => owned,
+            None => return false
+        };
+
+        // the request was made before we owned the selection
+        if request.time != ffi::CurrentTime && request.time < owned.time {
+            return false;
+        }
+
+        if request.target != self.atoms.multiple {
+            return self.write_target(owned, request.requestor, request.target, property);
+        }
+
+        // the property holds pairs of target and property to convert, the properties of the
+        // targets that can't be converted are replaced with `None`
+        if request.property == 0 {
+            return false;
+        }
+        let mut pairs = self.read_atoms(request.requestor, property);
+        for pair in pairs.chunks_mut(2) {
+            if pair.len() == 2 && (pair[0] == self.atoms.multiple ||
+                                   !self.write_target(owned, request.requestor, pair[0], pair[1])) {
+                pair[1] = 0;
+            }
+        }
+        unsafe {
+            (self.display.xlib.XChangeProperty)(self.display.display, request.requestor, property,
+                                                self.atoms.atom_pair, 32, ffi::PropModeReplace,
+                                                pairs.as_ptr() as *const libc::c_uchar,
+                                                pairs.len() as libc::c_int);
+        }
+        true
+    }
+
+    /// Writes a target of a selection in a property of the requestor, returns false if the
+    /// target isn't available.
+    fn write_target(&self, owned: &OwnedSelection, requestor: ffi::Window, target: ffi::Atom,
+                    property: ffi::Atom) -> bool
+    {
+        if target == self.atoms.targets {
+            let mut targets = vec![self.atoms.targets, self.atoms.timestamp, self.atoms.multiple];
+            targets.extend(owned.contents.iter().map(|c| c.0));
+            unsafe {
+                (self.display.xlib.XChangeProperty)(self.display.display, requestor, property,
+                                                    ffi::XA_ATOM, 32, ffi::PropModeReplace,
+                                                    targets.as_ptr() as *const libc::c_uchar,
+                                                    targets.len() as libc::c_int);
+            }
+            return true;
+        }
+
+        if target == self.atoms.timestamp {
+            let time = owned.time as libc::c_long;
+            unsafe {
+                (self.display.xlib.XChangeProperty)(self.display.display, requestor, property,
+                                                    ffi::XA_INTEGER, 32, ffi::PropModeReplace,
+                                                    &time as *const libc::c_long as *const libc::c_uchar, 1);
+            }
+            return true;
+        }
+
+        let data = match owned.contents.iter().find(|c| c.0 == target) {
+            Some(&(_, ref data)) => data.clone(),
+            None => return false
+        };
+
+        unsafe {
+            if data.len() > INCR_CHUNK_SIZE {
+                // the chunks are sent every time the requestor deletes the property, the
+                // requestor may be another of our windows whose events must be kept
+                let mut attributes: ffi::XWindowAttributes = mem::zeroed();
+                (self.display.xlib.XGetWindowAttributes)(self.display.display, requestor, &mut attributes);
+                (self.display.xlib.XSelectInput)(self.display.display, requestor,
+                                                 attributes.your_event_mask | ffi::PropertyChangeMask);
+                let size = data.len() as libc::c_long;
+                (self.display.xlib.XChangeProperty)(self.display.display, requestor, property,
+                                                    self.atoms.incr, 32, ffi::PropModeReplace,
+                                                    &size as *const libc::c_long as *const libc::c_uchar, 1);
+                self.transfers.lock().unwrap().push(IncrTransfer {
+                    requestor: requestor,
+                    property: property,
+                    target: target,
+                    data: data,
+                    offset: 0,
+                });
+            } else {
+                (self.display.xlib.XChangeProperty)(self.display.display, requestor, property,
+                                                    target, 8, ffi::PropModeReplace,
+                                                    data.as_ptr(), data.len() as libc::c_int);
+            }
+        }
+
+        true
+    }
+
+    /// Reads a property of another window holding atoms, like the pairs of a `MULTIPLE` request.
+    fn read_atoms(&self, window: ffi::Window, property: ffi::Atom) -> Vec<ffi::Atom> {
+        let mut property_type = 0;
+        let mut format = 0;
+        let mut items = 0;
+        let mut bytes_after = 0;
+        let mut data: *mut libc::c_uchar = ptr::null_mut();
+
+        unsafe {
+            (self.display.xlib.XGetWindowProperty)(self.display.display, window, property,
+                                                   0, libc::c_long::max_value() / 4, ffi::False,
+                                                   ffi::AnyPropertyType as ffi::Atom,
+                                                   &mut property_type, &mut format, &mut items,
+                                                   &mut bytes_after, &mut data);
+        }
+
+        // the window may have been destroyed in the meantime
+        if self.display.check_errors().is_err() || data.is_null() {
+            return Vec::new();
+        }
+
+        // items of format 32 are stored as longs by xlib
+        let atoms = if format == 32 {
+            unsafe { slice::from_raw_parts(data as *const ffi::Atom, items as usize).to_vec() }
+        } else {
+            Vec::new()
+        };
+        unsafe { (self.display.xlib.XFree)(data as *mut _) };
+        atoms
+    }
+
+    /// Sends the next chunk of an INCR transfer after the requestor read the previous one.
+    fn continue_transfer(&self, requestor: ffi::Window, property: ffi::Atom) {
+        let mut transfers = self.transfers.lock().unwrap();
+        let index = match transfers.iter().position(|t| t.requestor == requestor && t.property == property) {
+            Some(index) => index,
+            None => return
+        };
+
+        let finished = {
+            let transfer = &mut transfers[index];
+            let end = cmp::min(transfer.offset + INCR_CHUNK_SIZE, transfer.data.len());
+            let chunk = &transfer.data[transfer.offset..end];
+
+            // an empty chunk marks the end of the transfer
+            unsafe {
+                (self.display.xlib.XChangeProperty)(self.display.display, requestor, property,
+                                                    transfer.target, 8, ffi::PropModeReplace,
+                                                    chunk.as_ptr(), chunk.len() as libc::c_int);
+                (self.display.xlib.XFlush)(self.display.display);
+            }
+            self.display.ignore_error();
+
+            let finished = chunk.is_empty();
+            transfer.offset = end;
+            finished
+        };
+
+        if finished {
+            transfers.remove(index);
+        }
+    }
+}
+
+impl Drop for Clipboard {
+    fn drop(&mut self) {
+        OWNED_SELECTIONS.lock().unwrap().retain(|owned| owned.window != self.window);
+    }
+}
+
+/// Reads the atoms of a property of format 32, which xlib stores as longs.
+fn atoms_from_bytes(data: &[u8]) -> Vec<ffi::Atom> {
+    let size = mem::size_of::<ffi::Atom>();
+    data.chunks(size).filter(|chunk| chunk.len() == size).map(|chunk| {
+        let mut atom: ffi::Atom = 0;
+        unsafe { ptr::copy_nonoverlapping(chunk.as_ptr(), &mut atom as *mut ffi::Atom as *mut u8, size) };
+        atom
+    }).collect()
+}
+
+unsafe extern "C" fn event_matches(_: *mut ffi::Display, xev: *mut ffi::XEvent,
+                                   arg: ffi::XPointer) -> libc::c_int
+{
+    let filter = &*(arg as *const EventFilter);
+    let xev = &*xev;
+
+    if xev.get_type() != filter.event_type {
+        return ffi::False;
+    }
+
+    let matches = match filter.event_type {
+        ffi::SelectionNotify => {
+            let notify: &ffi::XSelectionEvent = mem::transmute(xev);
+            notify.requestor == filter.window && notify.selection == filter.atom
+        },
+        ffi::PropertyNotify => {
+            let property: &ffi::XPropertyEvent = mem::transmute(xev);
+            property.window == filter.window && property.atom == filter.atom &&
+                property.state == filter.state
+        },
+        _ => false
+    };
+
+    if matches { ffi::True } else { ffi::False }
+}
@@ -0,0 +1,479 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::{cmp, mem, ptr, slice};
+
+use libc;
+
+use Event;
+use api::uri_list;
+
+use super::clipboard::Clipboard;
+use super::ffi;
+use super::XConnection;
+
+/// Version of the XDND protocol that we implement.
+const XDND_VERSION: libc::c_long = 5;
+
+struct DndAtoms {
+    aware: ffi::Atom,
+    enter: ffi::Atom,
+    position: ffi::Atom,
+    status: ffi::Atom,
+    leave: ffi::Atom,
+    drop: ffi::Atom,
+    finished: ffi::Atom,
+    action_copy: ffi::Atom,
+    type_list: ffi::Atom,
+    selection: ffi::Atom,
+    uri_list: ffi::Atom,
+    incr: ffi::Atom,
+    /// Property of our window in which the source writes the files
+    property: ffi::Atom,
+}
+
+/// State of the drag that is currently over the window.
+struct DndState {
+    /// Window the drag comes from
+    source: Option<ffi::Window>,
+    /// Whether the source provides `text/uri-list`
+    has_uri_list: bool,
+    /// Files being dragged, requested on the first position message
+    paths: Option<Vec<PathBuf>>,
+    /// Time of the conversion of the selection that we wait for, if any
+    request: Option<ffi::Time>,
+    /// Whether the drop happened before the files arrived
+    dropped: bool,
+}
+
+/// State of a drag started from the window.
+struct OutgoingDrag {
+    /// Targets the dragged data can be converted to
+    types: Vec<ffi::Atom>,
+    /// XDND-aware window under the pointer, with the version of the protocol it supports
+    target: Option<(ffi::Window, libc::c_long)>,
+    /// Whether the target said it would accept a drop
+    accepted: bool,
+    /// Whether the drop was sent and we wait for the target to finish
+    dropped: bool,
+}
+
+/// Implements the XDND protocol, turning file drops into events and sending our own drags to
+/// other windows.
+pub struct Dnd {
+    display: Arc<XConnection>,
+    window: ffi::Window,
+    atoms: DndAtoms,
+    state: Mutex<DndState>,
+    outgoing: Mutex<Option<OutgoingDrag>>,
+}
+
+impl Dnd {
+    /// Announces to the sources that the window accepts drops.
+    pub fn new(display: &Arc<XConnection>, window: ffi::Window) -> Dnd {
+        let atoms = DndAtoms {
+            aware: display.get_atom("XdndAware"),
+            enter: display.get_atom("XdndEnter"),
+            position: display.get_atom("XdndPosition"),
+            status: display.get_atom("XdndStatus"),
+            leave: display.get_atom("XdndLeave"),
+            drop: display.get_atom("XdndDrop"),
+            finished: display.get_atom("XdndFinished"),
+            action_copy: display.get_atom("XdndActionCopy"),
+            type_list: display.get_atom("XdndTypeList"),
+            selection: display.get_atom("XdndSelection"),
+            uri_list: display.get_atom(uri_list::MIME_TYPE),
+            incr: display.get_atom("INCR"),
+            property: display.get_atom("_WINIT_DND_SELECTION"),
+        };
+        display.check_errors().expect("Failed to call XInternAtom");
+
+        unsafe {
+            (display.xlib.XChangeProperty)(display.display, window, atoms.aware, ffi::XA_ATOM, 32,
+                                           ffi::PropModeReplace,
+                                           &XDND_VERSION as *const libc::c_long as *const libc::c_uchar, 1);
+        }
+        display.check_errors().expect("Failed to set XdndAware");
+
+        Dnd {
+            display: display.clone(),
+            window: window,
+            atoms: atoms,
+            state: Mutex::new(DndState {
+                source: None,
+                has_uri_list: false,
+                paths: None,
+                request: None,
+                dropped: false,
+            }),
+            outgoing: Mutex::new(None),
+        }
+    }
+
+    /// Returns true if the client message is part of the XDND protocol.
+    pub fn is_dnd_message(&self, message: &ffi::XClientMessageEvent) -> bool {
+        let message_type = message.message_type;
+        message_type == self.atoms.enter || message_type == self.atoms.position ||
+            message_type == self.atoms.leave || message_type == self.atoms.drop ||
+            message_type == self.atoms.status || message_type == self.atoms.finished
+    }
+
+    /// Processes a message from the source or the target of a drag, and returns the resulting
+    /// events.
+    ///
+    /// The files are requested from the source without waiting for them, they arrive later in a
+    /// `SelectionNotify` event that is given to `handle_selection_notify`.
+    pub fn handle_message(&self, message: &ffi::XClientMessageEvent) -> Vec<Event> {
+        if message.message_type == self.atoms.status || message.message_type == self.atoms.finished {
+            return self.handle_target_message(message);
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let source = message.data.get_long(0) as ffi::Window;
+        let mut events = Vec::new();
+
+        if message.message_type == self.atoms.enter {
+            // the first three types are in the message, the others in a property of the source
+            let types = if message.data.get_long(1) & 1 != 0 {
+                self.read_atom_property(source, self.atoms.type_list)
+            } else {
+                (2..5).map(|i| message.data.get_long(i) as ffi::Atom).collect()
+            };
+
+            state.source = Some(source);
+            state.has_uri_list = types.contains(&self.atoms.uri_list);
+            state.paths = None;
+            state.request = None;
+            state.dropped = false;
+            return events;
+        }
+
+        if state.source != Some(source) {
+            return events;
+        }
+
+        if message.message_type == self.atoms.position {
+            self.request_paths(&mut state, message.data.get_long(3) as ffi::Time);
+
+            // the drop is accepted until we know that no file is being dragged
+            let accepted = state.has_uri_list && state.paths.as_ref().map_or(true, |paths| !paths.is_empty());
+            let action = if accepted { self.atoms.action_copy } else { 0 };
+            self.send_message(source, self.atoms.status,
+                              [self.window as libc::c_long, accepted as libc::c_long, 0, 0,
+                               action as libc::c_long]);
+
+        } else if message.message_type == self.atoms.leave {
+            if state.paths.as_ref().map_or(false, |paths| !paths.is_empty()) {
+                events.push(Event::HoveredFileCancelled);
+            }
+            state.source = None;
+            state.paths = None;
+            state.request = None;
+
+        } else if message.message_type == self.atoms.drop {
+            if state.paths.is_some() || !state.has_uri_list {
+                events.extend(self.finish_drop(&mut state));
+            } else {
+                // the drop is finished once the files arrive
+                state.dropped = true;
+                self.request_paths(&mut state, message.data.get_long(2) as ffi::Time);
+            }
+        }
+
+        events
+    }
+
+    /// Receives the files being dragged, that were requested while processing a message from
+    /// the source, and returns the resulting events.
+    pub fn handle_selection_notify(&self, notify: &ffi::XSelectionEvent, clipboard: &Clipboard) -> Vec<Event> {
+        if notify.requestor != self.window || notify.selection != self.atoms.selection {
+            return Vec::new();
+        }
+
+        let mut state = self.state.lock().unwrap();
+        // the answer to a request of a previous drag
+        if state.source.is_none() || state.request != Some(notify.time) {
+            return Vec::new();
+        }
+        state.request = None;
+
+        // the source couldn't convert the selection, and lists of files are too small to be
+        // sent in chunks, which we don't support
+        let paths = match clipboard.read_property(self.atoms.property) {
+            Some((property_type, _)) if notify.property == 0 || property_type == self.atoms.incr => Vec::new(),
+            Some((_, data)) => uri_list::decode(&data),
+            None => Vec::new()
+        };
+
+        if state.dropped {
+            state.paths = Some(paths);
+            self.finish_drop(&mut state)
+        } else {
+            let events = paths.iter().cloned().map(Event::HoveredFile).collect();
+            state.paths = Some(paths);
+            events
+        }
+    }
+
+    /// Asks the source to write the files being dragged in our property, unless it was done
+    /// already.
+    fn request_paths(&self, state: &mut DndState, time: ffi::Time) {
+        if !state.has_uri_list || state.paths.is_some() || state.request.is_some() {
+            return;
+        }
+
+        unsafe {
+            (self.display.xlib.XConvertSelection)(self.display.display, self.atoms.selection,
+                                                  self.atoms.uri_list, self.atoms.property,
+                                                  self.window, time);
+            (self.display.xlib.XFlush)(self.display.display);
+        }
+        self.display.check_errors().expect("Failed to call XConvertSelection");
+        state.request = Some(time);
+    }
+
+    /// Generates the events of the drop and tells the source that it is finished.
+    fn finish_drop(&self, state: &mut DndState) -> Vec<Event> {
+        let source = match state.source.take() {
+            Some(source) => source,
+            None => return Vec::new()
+        };
+        let paths = state.paths.take().unwrap_or_else(Vec::new);
+        state.dropped = false;
+
+        let accepted = !paths.is_empty();
+        let action = if accepted { self.atoms.action_copy } else { 0 };
+        self.send_message(source, self.atoms.finished,
+                          [self.window as libc::c_long, accepted as libc::c_long,
+                           action as libc::c_long, 0, 0]);
+
+        paths.into_iter().map(Event::DroppedFile).collect()
+    }
+
+    /// Starts dragging data in several MIME types from the window, until the pointer button is
+    /// released.
+    pub fn start_drag(&self, contents: Vec<(String, Vec<u8>)>, clipboard: &Clipboard) -> Vec<Event> {
+        let mut outgoing = self.outgoing.lock().unwrap();
+        if outgoing.is_some() {
+            return vec![Event::DragCancelled];
+        }
+
+        let types = clipboard.set_selection_contents(self.atoms.selection, contents);
+        if types.is_empty() {
+            return vec![Event::DragCancelled];
+        }
+
+        // the pointer events must come to us wherever the pointer goes during the drag
+        let grab = unsafe {
+            (self.display.xlib.XGrabPointer)(self.display.display, self.window, ffi::False,
+                                             (ffi::ButtonReleaseMask | ffi::PointerMotionMask) as libc::c_uint,
+                                             ffi::GrabModeAsync, ffi::GrabModeAsync, 0, 0,
+                                             ffi::CurrentTime)
+        };
+        self.display.check_errors().expect("Failed to call XGrabPointer");
+        if grab != ffi::GrabSuccess {
+            return vec![Event::DragCancelled];
+        }
+
+        // targets read the types that don't fit in the enter message from this property
+        unsafe {
+            (self.display.xlib.XChangeProperty)(self.display.display, self.window, self.atoms.type_list,
+                                                ffi::XA_ATOM, 32, ffi::PropModeReplace,
+                                                types.as_ptr() as *const libc::c_uchar,
+                                                types.len() as libc::c_int);
+        }
+        self.display.check_errors().expect("Failed to set XdndTypeList");
+
+        *outgoing = Some(OutgoingDrag {
+            types: types,
+            target: None,
+            accepted: false,
+            dropped: false,
+        });
+        Vec::new()
+    }
+
+    /// Follows the pointer during a drag started from the window, and returns the resulting
+    /// events.
+    pub fn handle_pointer_event(&self, xev: &ffi::XEvent) -> Vec<Event> {
+        match xev.get_type() {
+            ffi::MotionNotify => {
+                let motion: &ffi::XMotionEvent = unsafe { mem::transmute(xev) };
+                self.drag_motion(motion.x_root, motion.y_root, motion.time);
+                Vec::new()
+            },
+            ffi::ButtonRelease => {
+                let button: &ffi::XButtonEvent = unsafe { mem::transmute(xev) };
+                self.drag_release(button.time)
+            },
+            _ => Vec::new()
+        }
+    }
+
+    fn drag_motion(&self, x: libc::c_int, y: libc::c_int, time: ffi::Time) {
+        let mut outgoing = self.outgoing.lock().unwrap();
+        let drag = match *outgoing {
+            Some(ref mut drag) if !drag.dropped => drag,
+            _ => return
+        };
+
+        let target = self.find_target(x, y);
+        if target.map(|t| t.0) != drag.target.map(|t| t.0) {
+            if let Some((old_target, _)) = drag.target {
+                self.send_message(old_target, self.atoms.leave, [self.window as libc::c_long, 0, 0, 0, 0]);
+            }
+            drag.accepted = false;
+
+            if let Some((new_target, version)) = target {
+                let version = cmp::min(version, XDND_VERSION);
+                let more_types = if drag.types.len() > 3 { 1 } else { 0 };
+                let type_at = |i: usize| drag.types.get(i).cloned().unwrap_or(0) as libc::c_long;
+                self.send_message(new_target, self.atoms.enter,
+                                  [self.window as libc::c_long, (version << 24) | more_types,
+                                   type_at(0), type_at(1), type_at(2)]);
+            }
+            drag.target = target;
+        }
+
+        if let Some((target, _)) = drag.target {
+            let position = ((x as libc::c_long & 0xffff) << 16) | (y as libc::c_long & 0xffff);
+            self.send_message(target, self.atoms.position,
+                              [self.window as libc::c_long, 0, position, time as libc::c_long,
+                               self.atoms.action_copy as libc::c_long]);
+        }
+    }
+
+    fn drag_release(&self, time: ffi::Time) -> Vec<Event> {
+        let mut outgoing = self.outgoing.lock().unwrap();
+        let cancelled = match *outgoing {
+            Some(ref mut drag) if !drag.dropped => {
+                match drag.target {
+                    Some((target, _)) if drag.accepted => {
+                        self.send_message(target, self.atoms.drop,
+                                          [self.window as libc::c_long, 0, time as libc::c_long, 0, 0]);
+                        drag.dropped = true;
+                        false
+                    },
+                    Some((target, _)) => {
+                        self.send_message(target, self.atoms.leave, [self.window as libc::c_long, 0, 0, 0, 0]);
+                        true
+                    },
+                    None => true
+                }
+            },
+            _ => return Vec::new()
+        };
+
+        unsafe { (self.display.xlib.XUngrabPointer)(self.display.display, ffi::CurrentTime) };
+        self.display.check_errors().expect("Failed to call XUngrabPointer");
+
+        if cancelled {
+            *outgoing = None;
+            vec![Event::DragCancelled]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Processes a message from the target of a drag started from the window.
+    fn handle_target_message(&self, message: &ffi::XClientMessageEvent) -> Vec<Event> {
+        let mut outgoing = self.outgoing.lock().unwrap();
+        let target = message.data.get_long(0) as ffi::Window;
+
+        let finished = match *outgoing {
+            Some(ref mut drag) if drag.target.map(|t| t.0) == Some(target) => {
+                if message.message_type == self.atoms.status {
+                    drag.accepted = message.data.get_long(1) & 1 != 0;
+                    None
+                } else if drag.dropped {
+                    // targets older than version 5 don't tell whether they accepted the drop
+                    let version = drag.target.map_or(0, |t| t.1);
+                    Some(version < 5 || message.data.get_long(1) & 1 != 0)
+                } else {
+                    None
+                }
+            },
+            _ => None
+        };
+
+        match finished {
+            Some(accepted) => {
+                *outgoing = None;
+                vec![if accepted { Event::DragDropped } else { Event::DragCancelled }]
+            },
+            None => Vec::new()
+        }
+    }
+
+    /// Returns the XDND-aware window at a position of the screen, with the version of the
+    /// protocol it supports.
+    fn find_target(&self, x: libc::c_int, y: libc::c_int) -> Option<(ffi::Window, libc::c_long)> {
+        let root = unsafe { (self.display.xlib.XDefaultRootWindow)(self.display.display) };
+        let mut current = root;
+
+        loop {
+            let mut child_x = 0;
+            let mut child_y = 0;
+            let mut child = 0;
+            unsafe {
+                (self.display.xlib.XTranslateCoordinates)(self.display.display, root, current, x, y,
+                                                          &mut child_x, &mut child_y, &mut child);
+            }
+            if self.display.check_errors().is_err() || child == 0 {
+                return None;
+            }
+
+            if let Some(&version) = self.read_atom_property(child, self.atoms.aware).first() {
+                return Some((child, version as libc::c_long));
+            }
+            current = child;
+        }
+    }
+
+    /// Reads a property holding a list of atoms, like `XdndTypeList` or `XdndAware`.
+    fn read_atom_property(&self, window: ffi::Window, property: ffi::Atom) -> Vec<ffi::Atom> {
+        let mut property_type = 0;
+        let mut format = 0;
+        let mut items = 0;
+        let mut bytes_after = 0;
+        let mut data: *mut libc::c_uchar = ptr::null_mut();
+
+        unsafe {
+            (self.display.xlib.XGetWindowProperty)(self.display.display, window, property,
+                                                   0, libc::c_long::max_value() / 4, ffi::False,
+                                                   ffi::XA_ATOM, &mut property_type, &mut format,
+                                                   &mut items, &mut bytes_after, &mut data);
+        }
+
+        // the window may have been destroyed in the meantime
+        if self.display.check_errors().is_err() || data.is_null() {
+            return Vec::new();
+        }
+
+        let types = unsafe { slice::from_raw_parts(data as *const ffi::Atom, items as usize).to_vec() };
+        unsafe { (self.display.xlib.XFree)(data as *mut _) };
+        types
+    }
+
+    fn send_message(&self, target: ffi::Window, message_type: ffi::Atom, data: [libc::c_long; 5]) {
+        let mut xev = ffi::XClientMessageEvent {
+            type_: ffi::ClientMessage,
+            window: target,
+            format: 32,
+            message_type: message_type,
+            serial: 0,
+            send_event: ffi::True,
+            display: self.display.display,
+            data: unsafe { mem::zeroed() },
+        };
+        for (i, &value) in data.iter().enumerate() {
+            xev.data.set_long(i, value);
+        }
+
+        unsafe {
+            (self.display.xlib.XSendEvent)(self.display.display, target, ffi::False, ffi::NoEventMask,
+                                           &mut xev as *mut ffi::XClientMessageEvent as *mut ffi::XEvent);
+            (self.display.xlib.XFlush)(self.display.display);
+        }
+        self.display.ignore_error();
+    }
+}
@@ -5,3 +5,135 @@ pub use x11_dl::xlib::*;
 pub use x11_dl::xinput::*;
 pub use x11_dl::xinput2::*;
 pub use x11_dl::error::OpenError;
+
+use std::os::raw::{c_double, c_int, c_ulong, c_ushort, c_void};
+
+// XInput 2.4 gesture events, which are not part of x11-dl yet.
+pub const XI_2_4_Minor: c_int = 4;
+
+pub const XI_GesturePinchBegin: c_int = 27;
+pub const XI_GesturePinchUpdate: c_int = 28;
+pub const XI_GesturePinchEnd: c_int = 29;
+pub const XI_GestureSwipeBegin: c_int = 30;
+pub const XI_GestureSwipeUpdate: c_int = 31;
+pub const XI_GestureSwipeEnd: c_int = 32;
+
+pub const XIGesturePinchEventCancelled: c_int = 1 << 0;
+pub const XIGestureSwipeEventCancelled: c_int = 1 << 0;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct XIGesturePinchEvent {
+    pub type_: c_int,
+    pub serial: c_ulong,
+    pub send_event: Bool,
+    pub display: *mut Display,
+    pub extension: c_int,
+    pub evtype: c_int,
+    pub time: Time,
+    pub deviceid: c_int,
+    pub sourceid: c_int,
+    pub detail: c_int,
+    pub root: Window,
+    pub event: Window,
+    pub child: Window,
+    pub root_x: c_double,
+    pub root_y: c_double,
+    pub event_x: c_double,
+    pub event_y: c_double,
+    pub delta_x: c_double,
+    pub delta_y: c_double,
+    pub delta_unaccel_x: c_double,
+    pub delta_unaccel_y: c_double,
+    pub scale: c_double,
+    pub delta_angle: c_double,
+    pub flags: c_int,
+    pub mods: XIModifierState,
+    pub group: XIGroupState,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct XIGestureSwipeEvent {
+    pub type_: c_int,
+    pub serial: c_ulong,
+    pub send_event: Bool,
+    pub display: *mut Display,
+    pub extension: c_int,
+    pub evtype: c_int,
+    pub time: Time,
+    pub deviceid: c_int,
+    pub sourceid: c_int,
+    pub detail: c_int,
+    pub root: Window,
+    pub event: Window,
+    pub child: Window,
+    pub root_x: c_double,
+    pub root_y: c_double,
+    pub event_x: c_double,
+    pub event_y: c_double,
+    pub delta_x: c_double,
+    pub delta_y: c_double,
+    pub delta_unaccel_x: c_double,
+    pub delta_unaccel_y: c_double,
+    pub flags: c_int,
+    pub mods: XIModifierState,
+    pub group: XIGroupState,
+}
+
+// XIM types used by the preedit callbacks, which are not part of x11-dl yet.
+pub type XIMStyle = c_ulong;
+pub type XIMFeedback = c_ulong;
+
+pub type XICProc = Option<unsafe extern "C" fn(XIC, XPointer, XPointer) -> Bool>;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct XIMStyles {
+    pub count_styles: c_ushort,
+    pub supported_styles: *mut XIMStyle,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct XICCallback {
+    pub client_data: XPointer,
+    pub callback: XICProc,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct XIMText {
+    pub length: c_ushort,
+    pub feedback: *mut XIMFeedback,
+    pub encoding_is_wchar: Bool,
+    /// Either a `char *` or a `wchar_t *` depending on `encoding_is_wchar`
+    pub string: *mut c_void,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct XIMPreeditDrawCallbackStruct {
+    pub caret: c_int,
+    pub chg_first: c_int,
+    pub chg_length: c_int,
+    pub text: *mut XIMText,
+}
+
+pub type XIMCaretDirection = c_int;
+pub const XIMForwardChar: XIMCaretDirection = 0;
+pub const XIMBackwardChar: XIMCaretDirection = 1;
+pub const XIMLineStart: XIMCaretDirection = 8;
+pub const XIMLineEnd: XIMCaretDirection = 9;
+pub const XIMAbsolutePosition: XIMCaretDirection = 10;
+
+pub type XIMCaretStyle = c_int;
+pub const XIMIsInvisible: XIMCaretStyle = 0;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct XIMPreeditCaretCallbackStruct {
+    pub position: c_int,
+    pub direction: XIMCaretDirection,
+    pub style: XIMCaretStyle,
+}
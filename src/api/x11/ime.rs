@@ -0,0 +1,253 @@
+use std::collections::VecDeque;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::sync::Mutex;
+use std::{cmp, ptr, slice};
+
+use libc;
+
+use Event;
+
+use super::ffi;
+use super::XConnection;
+
+/// State of the text being composed, shared with the XIM preedit callbacks.
+///
+/// The address of this object is given to the input method as client data, so it must not
+/// move or be dropped for as long as the input context that uses it exists.
+pub struct ImeState {
+    inner: Mutex<ImeInner>,
+}
+
+struct ImeInner {
+    /// The text being composed.
+    preedit: Vec<char>,
+    /// Position of the caret in `preedit`, in characters.
+    caret: usize,
+    caret_visible: bool,
+    composing: bool,
+    /// Events generated by the callbacks that haven't been dispatched yet.
+    events: VecDeque<Event>,
+}
+
+unsafe impl Send for ImeState {}
+unsafe impl Sync for ImeState {}
+
+impl ImeState {
+    pub fn new() -> ImeState {
+        ImeState {
+            inner: Mutex::new(ImeInner {
+                preedit: Vec::new(),
+                caret: 0,
+                caret_visible: true,
+                composing: false,
+                events: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Returns true if the input method is in the middle of composing some text.
+    pub fn is_composing(&self) -> bool {
+        self.inner.lock().unwrap().composing
+    }
+
+    /// Forgets the text being composed, for when the input method is disabled in the middle
+    /// of a composition.
+    pub fn cancel_composition(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.preedit.clear();
+        inner.caret = 0;
+        inner.composing = false;
+    }
+
+    /// Removes the events that were generated by the input method since the last call.
+    pub fn take_events(&self) -> Vec<Event> {
+        self.inner.lock().unwrap().events.drain(..).collect()
+    }
+}
+
+/// Creates an input context for `window`.
+///
+/// Tries the on-the-spot style first so that the preedit text is reported through the
+/// callbacks of `state`, then over-the-spot so that the input method draws it at the spot
+/// location, and falls back to letting the input method handle everything itself.
+pub unsafe fn create_input_context(display: &XConnection, im: ffi::XIM, window: ffi::Window,
+                                   state: &ImeState) -> ffi::XIC
+{
+    let callbacks_style = (ffi::XIMPreeditCallbacks | ffi::XIMStatusNothing) as ffi::XIMStyle;
+    let position_style = (ffi::XIMPreeditPosition | ffi::XIMStatusNothing) as ffi::XIMStyle;
+    let nothing_style = (ffi::XIMPreeditNothing | ffi::XIMStatusNothing) as ffi::XIMStyle;
+
+    let styles = supported_styles(display, im);
+    let style = if styles.contains(&callbacks_style) {
+        callbacks_style
+    } else if styles.contains(&position_style) {
+        position_style
+    } else {
+        nothing_style
+    };
+
+    // the callback structures are copied by Xlib, only `state` has to outlive the context
+    let client_data = state as *const ImeState as ffi::XPointer;
+    let start = ffi::XICCallback { client_data: client_data, callback: Some(preedit_start_callback) };
+    let done = ffi::XICCallback { client_data: client_data, callback: Some(preedit_done_callback) };
+    let draw = ffi::XICCallback { client_data: client_data, callback: Some(preedit_draw_callback) };
+    let caret = ffi::XICCallback { client_data: client_data, callback: Some(preedit_caret_callback) };
+
+    let mut spot = ffi::XPoint { x: 0, y: 0 };
+    let preedit_attributes = if style == callbacks_style {
+        (display.xlib.XVaCreateNestedList)(0,
+            b"preeditStartCallback\0".as_ptr(), &start as *const ffi::XICCallback,
+            b"preeditDoneCallback\0".as_ptr(), &done as *const ffi::XICCallback,
+            b"preeditDrawCallback\0".as_ptr(), &draw as *const ffi::XICCallback,
+            b"preeditCaretCallback\0".as_ptr(), &caret as *const ffi::XICCallback,
+            ptr::null::<()>())
+    } else if style == position_style {
+        (display.xlib.XVaCreateNestedList)(0, b"spotLocation\0".as_ptr(), &mut spot as *mut ffi::XPoint, ptr::null::<()>())
+    } else {
+        ptr::null_mut()
+    };
+
+    if preedit_attributes.is_null() {
+        (display.xlib.XCreateIC)(im,
+            b"inputStyle\0".as_ptr(), style,
+            b"clientWindow\0".as_ptr(), window,
+            ptr::null::<()>())
+    } else {
+        let ic = (display.xlib.XCreateIC)(im,
+            b"inputStyle\0".as_ptr(), style,
+            b"clientWindow\0".as_ptr(), window,
+            b"preeditAttributes\0".as_ptr(), preedit_attributes,
+            ptr::null::<()>());
+        (display.xlib.XFree)(preedit_attributes);
+        ic
+    }
+}
+
+/// Tells the input method where the caret is, so that it can place its candidate window
+/// next to it.
+pub unsafe fn set_spot_location(display: &XConnection, ic: ffi::XIC, x: i32, y: i32) {
+    let mut spot = ffi::XPoint { x: x as libc::c_short, y: y as libc::c_short };
+    let preedit_attributes = (display.xlib.XVaCreateNestedList)(0,
+        b"spotLocation\0".as_ptr(), &mut spot as *mut ffi::XPoint, ptr::null::<()>());
+    if preedit_attributes.is_null() {
+        return;
+    }
+    (display.xlib.XSetICValues)(ic, b"preeditAttributes\0".as_ptr(), preedit_attributes,
+                                ptr::null::<()>());
+    (display.xlib.XFree)(preedit_attributes);
+}
+
+unsafe fn supported_styles(display: &XConnection, im: ffi::XIM) -> Vec<ffi::XIMStyle> {
+    let mut styles: *mut ffi::XIMStyles = ptr::null_mut();
+    let error = (display.xlib.XGetIMValues)(im, b"queryInputStyle\0".as_ptr(), &mut styles as *mut *mut ffi::XIMStyles,
+                                            ptr::null::<()>());
+    if !error.is_null() || styles.is_null() {
+        return Vec::new();
+    }
+
+    let result = slice::from_raw_parts((*styles).supported_styles,
+                                       (*styles).count_styles as usize).to_vec();
+    (display.xlib.XFree)(styles as *mut _);
+    result
+}
+
+unsafe fn ime_state<'a>(client_data: ffi::XPointer) -> &'a ImeState {
+    &*(client_data as *const ImeState)
+}
+
+impl ImeInner {
+    fn push_preedit_event(&mut self) {
+        let text: String = self.preedit.iter().cloned().collect();
+        let cursor = if self.caret_visible {
+            let offset = self.preedit[..self.caret].iter().fold(0, |offset, c| offset + c.len_utf8());
+            Some((offset, offset))
+        } else {
+            None
+        };
+        self.events.push_back(Event::ImePreedit(text, cursor));
+    }
+}
+
+/// Decodes the text sent by the input method in a draw callback.
+unsafe fn decode_text(text: &ffi::XIMText) -> Vec<char> {
+    if text.string.is_null() {
+        return Vec::new();
+    }
+
+    if text.encoding_is_wchar != 0 {
+        let chars = slice::from_raw_parts(text.string as *const libc::wchar_t, text.length as usize);
+        chars.iter().filter_map(|&c| ::std::char::from_u32(c as u32)).collect()
+    } else {
+        CStr::from_ptr(text.string as *const c_char).to_string_lossy().chars().collect()
+    }
+}
+
+unsafe extern "C" fn preedit_start_callback(_: ffi::XIC, client_data: ffi::XPointer,
+                                            _: ffi::XPointer) -> ffi::Bool
+{
+    let mut inner = ime_state(client_data).inner.lock().unwrap();
+    inner.preedit.clear();
+    inner.caret = 0;
+    inner.caret_visible = true;
+    inner.composing = true;
+
+    // no limit on the length of the preedit text
+    -1
+}
+
+unsafe extern "C" fn preedit_done_callback(_: ffi::XIC, client_data: ffi::XPointer,
+                                           _: ffi::XPointer) -> ffi::Bool
+{
+    let mut inner = ime_state(client_data).inner.lock().unwrap();
+    inner.preedit.clear();
+    inner.caret = 0;
+    inner.composing = false;
+    inner.events.push_back(Event::ImePreedit(String::new(), None));
+    0
+}
+
+unsafe extern "C" fn preedit_draw_callback(_: ffi::XIC, client_data: ffi::XPointer,
+                                           call_data: ffi::XPointer) -> ffi::Bool
+{
+    let draw = &*(call_data as *const ffi::XIMPreeditDrawCallbackStruct);
+    let mut inner = ime_state(client_data).inner.lock().unwrap();
+
+    // replace the changed range with the new text
+    let len = inner.preedit.len();
+    let first = cmp::min(cmp::max(draw.chg_first, 0) as usize, len);
+    let last = cmp::min(first + cmp::max(draw.chg_length, 0) as usize, len);
+    let new_text = if draw.text.is_null() { Vec::new() } else { decode_text(&*draw.text) };
+    let tail = inner.preedit.split_off(last);
+    inner.preedit.truncate(first);
+    inner.preedit.extend(new_text);
+    inner.preedit.extend(tail);
+
+    inner.caret = cmp::min(cmp::max(draw.caret, 0) as usize, inner.preedit.len());
+    inner.composing = true;
+    inner.push_preedit_event();
+    0
+}
+
+unsafe extern "C" fn preedit_caret_callback(_: ffi::XIC, client_data: ffi::XPointer,
+                                            call_data: ffi::XPointer) -> ffi::Bool
+{
+    let caret = &mut *(call_data as *mut ffi::XIMPreeditCaretCallbackStruct);
+    let mut inner = ime_state(client_data).inner.lock().unwrap();
+
+    let len = inner.preedit.len();
+    inner.caret = match caret.direction {
+        ffi::XIMForwardChar => cmp::min(inner.caret + 1, len),
+        ffi::XIMBackwardChar => inner.caret.saturating_sub(1),
+        ffi::XIMLineStart => 0,
+        ffi::XIMLineEnd => len,
+        ffi::XIMAbsolutePosition => cmp::min(cmp::max(caret.position, 0) as usize, len),
+        _ => inner.caret,
+    };
+    inner.caret_visible = caret.style != ffi::XIMIsInvisible;
+
+    // the input method reads back the resulting position
+    caret.position = inner.caret as libc::c_int;
+    inner.push_preedit_event();
+    0
+}
@@ -1,23 +1,45 @@
 use std::sync::Arc;
 
 use libc;
-use std::{mem, ptr};
+use std::mem;
 use std::ffi::CString;
 use std::slice::from_raw_parts;
+use std::time::{Duration, Instant};
 
 use WindowAttributes;
 
-use events::Event;
+use events::{Event, MouseScrollDelta, TouchPhase};
+use events::{ContactEllipse, ElementState, Gesture, GestureKind, Pen, PenPhase, PenTool};
 
 use super::{events, ffi};
+use super::ime::ImeState;
 use super::XConnection;
 
+/// Time in milliseconds after which a touchpad scroll sequence that
+/// did not receive any new delta is considered to be over
+const SCROLL_SEQUENCE_TIMEOUT: ffi::Time = 150;
+
+/// Pixels scrolled by a touchpad moving by the `increment` of its scroll class.
+///
+/// The drivers set the increment to the finger movement that scrolls as much as one wheel
+/// click, and don't report how many pixels that is. Weston and Mutter scroll 10 pixels per
+/// wheel click on Wayland, so touchpads scroll at the same speed on both.
+const PIXELS_PER_SCROLL_INCREMENT: f64 = 10.0;
+
 #[derive(Debug)]
 enum AxisType {
     HorizontalScroll,
     VerticalScroll
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScrollSource {
+    /// A wheel with distinct 'clicks', reported as lines
+    Wheel,
+    /// A touchpad or any other continuous scroller, reported as pixels
+    Touchpad,
+}
+
 #[derive(Debug)]
 struct Axis {
     id: i32,
@@ -25,6 +47,73 @@ struct Axis {
     axis_number: i32,
     axis_type: AxisType,
     scroll_increment: f64,
+    scroll_source: ScrollSource,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ValuatorRange {
+    number: i32,
+    min: f64,
+    max: f64,
+}
+
+impl ValuatorRange {
+    /// Maps a raw valuator value to `0.0 ... 1.0`
+    fn normalize(&self, value: f64) -> f64 {
+        if self.max > self.min {
+            ((value - self.min) / (self.max - self.min)).max(0.0).min(1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Maps a raw valuator value to `-1.0 ... 1.0`, keeping `0.0` in the middle even if
+    /// the range isn't symmetric
+    fn normalize_centered(&self, value: f64) -> f64 {
+        let max = self.max.abs().max(self.min.abs());
+        if max > 0.0 {
+            (value / max).max(-1.0).min(1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PenDevice {
+    device_id: i32,
+    tool: PenTool,
+    pressure: ValuatorRange,
+    tilt_x: Option<ValuatorRange>,
+    tilt_y: Option<ValuatorRange>,
+    distance: Option<ValuatorRange>,
+    /// Last-seen state of the tool, as valuators are only
+    /// reported when they change
+    in_proximity: bool,
+    last_pressure: f64,
+    last_tilt: (f64, f64),
+    last_distance: Option<f64>,
+}
+
+#[derive(Debug)]
+struct TouchDevice {
+    device_id: i32,
+    pressure: Option<ValuatorRange>,
+    touch_major: Option<ValuatorRange>,
+    touch_minor: Option<ValuatorRange>,
+    orientation: Option<ValuatorRange>,
+}
+
+/// Last-seen valuators of a touch, as updates only carry the
+/// valuators that changed
+#[derive(Debug)]
+struct TouchValues {
+    device_id: i32,
+    touch_id: i32,
+    pressure: Option<f64>,
+    major: Option<f64>,
+    minor: Option<f64>,
+    orientation: Option<f64>,
 }
 
 #[derive(Debug)]
@@ -34,27 +123,104 @@ struct AxisValue {
     value: f64
 }
 
+#[derive(Debug)]
+struct ScrollSequence {
+    device_id: i32,
+    last_time: ffi::Time,
+}
+
+/// Touchpad scroll sequences that have been started but not ended yet, one per source device.
+///
+/// The server doesn't report when the fingers are lifted, so a sequence is over once it
+/// didn't receive any delta for `SCROLL_SEQUENCE_TIMEOUT`. Times are server times.
+#[derive(Debug, Default)]
+struct ScrollSequences {
+    sequences: Vec<ScrollSequence>,
+}
+
+impl ScrollSequences {
+    /// Records a delta of a device, and returns the phase to report it with along with
+    /// whether the previous sequence of the device timed out.
+    fn record(&mut self, device_id: i32, time: ffi::Time) -> (bool, TouchPhase) {
+        match self.sequences.iter().position(|s| s.device_id == device_id) {
+            Some(idx) => {
+                let timed_out = time.saturating_sub(self.sequences[idx].last_time) > SCROLL_SEQUENCE_TIMEOUT;
+                self.sequences[idx].last_time = time;
+                if timed_out {
+                    (true, TouchPhase::Started)
+                } else {
+                    (false, TouchPhase::Moved)
+                }
+            },
+            None => {
+                self.sequences.push(ScrollSequence { device_id: device_id, last_time: time });
+                (false, TouchPhase::Started)
+            }
+        }
+    }
+
+    /// Ends the sequence of a device, returns false if there was none.
+    fn end(&mut self, device_id: i32) -> bool {
+        let before = self.sequences.len();
+        self.sequences.retain(|s| s.device_id != device_id);
+        self.sequences.len() != before
+    }
+
+    /// Ends all the sequences, returns how many there were.
+    fn end_all(&mut self) -> usize {
+        self.sequences.drain(..).count()
+    }
+
+    /// Ends the sequences that timed out at `now`, returns how many there were.
+    fn end_timed_out(&mut self, now: ffi::Time) -> usize {
+        let before = self.sequences.len();
+        self.sequences.retain(|s| now.saturating_sub(s.last_time) <= SCROLL_SEQUENCE_TIMEOUT);
+        before - self.sequences.len()
+    }
+
+    /// Returns the time at which the next sequence times out, if any.
+    fn next_timeout(&self) -> Option<ffi::Time> {
+        self.sequences.iter().map(|s| s.last_time + SCROLL_SEQUENCE_TIMEOUT + 1).min()
+    }
+}
+
 struct InputState {
     /// Last-seen cursor position within a window in (x, y)
     /// coordinates
     cursor_pos: (f64, f64),
     /// Last-seen positions of axes, used to report delta
     /// movements when a new absolute axis value is received
-    axis_values: Vec<AxisValue>
+    axis_values: Vec<AxisValue>,
+    /// Touchpad scroll sequences that have been started but
+    /// not ended yet
+    scroll_sequences: ScrollSequences,
+    /// Server time of the latest scroll delta and when we received
+    /// it, to tell the current server time without a round trip
+    scroll_clock: Option<(ffi::Time, Instant)>,
+    /// Absolute scale of the current pinch gesture, used to
+    /// report scale deltas
+    pinch_scale: f64,
+    /// Touches that have begun but not ended yet
+    touches: Vec<TouchValues>,
 }
 
 pub struct XInputEventHandler {
     display: Arc<XConnection>,
     window: ffi::Window,
     ic: ffi::XIC,
+    ime_state: Arc<ImeState>,
+    /// False if the key events must not go through the input method
+    ime_allowed: bool,
     axis_list: Vec<Axis>,
+    pen_list: Vec<PenDevice>,
+    touch_list: Vec<TouchDevice>,
     current_state: InputState,
     multitouch: bool,
 }
 
 impl XInputEventHandler {
     pub fn new(display: &Arc<XConnection>, window: ffi::Window, ic: ffi::XIC,
-               window_attrs: &WindowAttributes) -> XInputEventHandler {
+               ime_state: Arc<ImeState>, window_attrs: &WindowAttributes) -> XInputEventHandler {
         // query XInput support
         let mut opcode: libc::c_int = 0;
         let mut event: libc::c_int = 0;
@@ -67,8 +233,10 @@ impl XInputEventHandler {
             }
         }
 
+        // ask for XInput 2.4 to receive gesture events, the server
+        // answers with the version it actually supports
         let mut xinput_major_ver = ffi::XI_2_Major;
-        let mut xinput_minor_ver = ffi::XI_2_Minor;
+        let mut xinput_minor_ver = ffi::XI_2_4_Minor;
 
         unsafe {
             if (display.xinput2.XIQueryVersion)(display.display, &mut xinput_major_ver, &mut xinput_minor_ver) != ffi::Success as libc::c_int {
@@ -80,7 +248,7 @@ impl XInputEventHandler {
         // Button clicks and mouse events are handled via XInput
         // events. Key presses are still handled via plain core
         // X11 events.
-        let mut mask: [libc::c_uchar; 3] = [0; 3];
+        let mut mask: [libc::c_uchar; 5] = [0; 5];
         let mut input_event_mask = ffi::XIEventMask {
             deviceid: ffi::XIAllMasterDevices,
             mask_len: mask.len() as i32,
@@ -101,6 +269,19 @@ impl XInputEventHandler {
         for event in events {
             ffi::XISetMask(&mut mask, *event);
         }
+        if xinput_major_ver > 2 || (xinput_major_ver == 2 && xinput_minor_ver >= ffi::XI_2_4_Minor) {
+            let gesture_events = &[
+                ffi::XI_GesturePinchBegin,
+                ffi::XI_GesturePinchUpdate,
+                ffi::XI_GesturePinchEnd,
+                ffi::XI_GestureSwipeBegin,
+                ffi::XI_GestureSwipeUpdate,
+                ffi::XI_GestureSwipeEnd,
+            ];
+            for event in gesture_events {
+                ffi::XISetMask(&mut mask, *event);
+            }
+        }
 
         unsafe {
             match (display.xinput2.XISelectEvents)(display.display, window, &mut input_event_mask, 1) {
@@ -109,48 +290,77 @@ impl XInputEventHandler {
             }
         }
 
+        let (axis_list, pen_list, touch_list) = read_input_device_info(display);
+
         XInputEventHandler {
             display: display.clone(),
             window: window,
             ic: ic,
-            axis_list: read_input_axis_info(display),
+            ime_state: ime_state,
+            ime_allowed: true,
+            axis_list: axis_list,
+            pen_list: pen_list,
+            touch_list: touch_list,
             current_state: InputState {
                 cursor_pos: (0.0, 0.0),
-                axis_values: Vec::new()
+                axis_values: Vec::new(),
+                scroll_sequences: ScrollSequences::default(),
+                scroll_clock: None,
+                pinch_scale: 1.0,
+                touches: Vec::new(),
             },
             multitouch: window_attrs.multitouch,
         }
     }
 
     pub fn translate_key_event(&self, event: &mut ffi::XKeyEvent) -> Vec<Event> {
-        use events::Event::{KeyboardInput, ReceivedCharacter};
+        use events::Event::{ImeCommit, KeyboardInput, ReceivedCharacter};
         use events::ElementState::{Pressed, Released};
 
         let mut translated_events = Vec::new();
 
-        let state;
-        if event.type_ == ffi::KeyPress {
+        // both presses and releases go through the input method, so that it sees
+        // complete dead-key and compose sequences
+        if self.ime_allowed {
             let raw_ev: *mut ffi::XKeyEvent = event;
-            unsafe { (self.display.xlib.XFilterEvent)(mem::transmute(raw_ev), self.window) };
-            state = Pressed;
-        } else {
-            state = Released;
+            let filtered = unsafe { (self.display.xlib.XFilterEvent)(mem::transmute(raw_ev), self.window) };
+            translated_events.extend(self.ime_state.take_events());
+
+            // the input method consumed the key to update the text being composed
+            if filtered == ffi::True {
+                return translated_events;
+            }
         }
 
+        let state = if event.type_ == ffi::KeyPress { Pressed } else { Released };
+
         let mut kp_keysym = 0;
 
         let written = unsafe {
-            use std::str;
-
-            let mut buffer: [u8; 16] = [mem::uninitialized(); 16];
             let raw_ev: *mut ffi::XKeyEvent = event;
-            let count = (self.display.xlib.Xutf8LookupString)(self.ic, mem::transmute(raw_ev),
-            mem::transmute(buffer.as_mut_ptr()),
-            buffer.len() as libc::c_int, &mut kp_keysym, ptr::null_mut());
+            let mut status = 0;
+            let mut buffer: Vec<u8> = vec![0; 16];
+            let mut count = (self.display.xlib.Xutf8LookupString)(self.ic, raw_ev,
+                buffer.as_mut_ptr() as *mut libc::c_char, buffer.len() as libc::c_int,
+                &mut kp_keysym, &mut status);
+
+            // committed text can be longer than the buffer, in which case the returned
+            // count is the size required to hold all of it
+            if status == ffi::XBufferOverflow {
+                buffer = vec![0; count as usize];
+                count = (self.display.xlib.Xutf8LookupString)(self.ic, raw_ev,
+                    buffer.as_mut_ptr() as *mut libc::c_char, buffer.len() as libc::c_int,
+                    &mut kp_keysym, &mut status);
+            }
 
-            str::from_utf8(&buffer[..count as usize]).unwrap_or("").to_string()
+            String::from_utf8_lossy(&buffer[..count as usize]).into_owned()
         };
 
+        // keys sent by the input method itself carry a null keycode
+        if !written.is_empty() && (event.keycode == 0 || self.ime_state.is_composing()) {
+            translated_events.push(ImeCommit(written.clone()));
+        }
+
         for chr in written.chars() {
             translated_events.push(ReceivedCharacter(chr));
         }
@@ -169,29 +379,59 @@ impl XInputEventHandler {
         translated_events
     }
 
-    pub fn translate_event(&mut self, cookie: &ffi::XGenericEventCookie) -> Option<Event> {
+    pub fn set_ime_allowed(&mut self, allowed: bool) {
+        if allowed == self.ime_allowed {
+            return;
+        }
+        self.ime_allowed = allowed;
+
+        unsafe {
+            if allowed {
+                (self.display.xlib.XSetICFocus)(self.ic);
+            } else {
+                // drop whatever was being composed
+                let preedit = (self.display.xlib.Xutf8ResetIC)(self.ic);
+                if !preedit.is_null() {
+                    (self.display.xlib.XFree)(preedit as *mut _);
+                }
+                (self.display.xlib.XUnsetICFocus)(self.ic);
+            }
+        }
+    }
+
+    pub fn translate_event(&mut self, cookie: &ffi::XGenericEventCookie) -> Vec<Event> {
         use events::Event::{Focused, MouseInput, MouseMoved, MouseWheel};
         use events::ElementState::{Pressed, Released};
         use events::MouseButton::{Left, Right, Middle};
-        use events::MouseScrollDelta::LineDelta;
-        use events::{Touch, TouchPhase};
+        use events::MouseScrollDelta::{LineDelta, PixelDelta};
+        use events::Touch;
+
+        let mut translated_events = Vec::new();
 
         match cookie.evtype {
             ffi::XI_ButtonPress | ffi::XI_ButtonRelease => {
                 let event_data: &ffi::XIDeviceEvent = unsafe{mem::transmute(cookie.data)};
                 if self.multitouch && (event_data.flags & ffi::XIPointerEmulated) != 0 {
                     // Deliver multi-touch events instead of emulated mouse events.
-                    return None
+                    return translated_events
                 }
                 let state = if cookie.evtype == ffi::XI_ButtonPress {
                     Pressed
                 } else {
                     Released
                 };
+                translated_events.extend(self.translate_pen_event(cookie.evtype, event_data));
                 match event_data.detail as u32 {
-                    ffi::Button1 => Some(MouseInput(state, Left)),
-                    ffi::Button2 => Some(MouseInput(state, Middle)),
-                    ffi::Button3 => Some(MouseInput(state, Right)),
+                    ffi::Button1 | ffi::Button2 | ffi::Button3 => {
+                        // a click ends any scroll sequence of the device
+                        translated_events.extend(self.end_scroll_sequence(event_data.sourceid));
+                        let button = match event_data.detail as u32 {
+                            ffi::Button1 => Left,
+                            ffi::Button2 => Middle,
+                            _ => Right,
+                        };
+                        translated_events.push(MouseInput(state, button));
+                    },
                     ffi::Button4 | ffi::Button5 => {
                         if event_data.flags & ffi::XIPointerEmulated == 0 {
                             // scroll event from a traditional wheel with
@@ -201,49 +441,65 @@ impl XInputEventHandler {
                             } else {
                                 -1.0
                             };
-                            Some(MouseWheel(LineDelta(0.0, delta), TouchPhase::Moved))
+                            translated_events.push(MouseWheel(LineDelta(0.0, delta), TouchPhase::Moved));
                         } else {
                             // emulated button event from a touch/smooth-scroll
                             // event. Ignore these events and handle scrolling
                             // via XI_Motion event handler instead
-                            None
                         }
                     }
-                    _ => None
+                    _ => {}
                 }
             },
             ffi::XI_Motion => {
                 let event_data: &ffi::XIDeviceEvent = unsafe{mem::transmute(cookie.data)};
                 if self.multitouch && (event_data.flags & ffi::XIPointerEmulated) != 0 {
                     // Deliver multi-touch events instead of emulated mouse events.
-                    return None
+                    return translated_events
                 }
+                translated_events.extend(self.translate_pen_event(cookie.evtype, event_data));
                 let axis_state = event_data.valuators;
                 let mask = unsafe{ from_raw_parts(axis_state.mask, axis_state.mask_len as usize) };
                 let mut axis_count = 0;
 
+                let mut scroll_source = None;
                 let mut scroll_delta = (0.0, 0.0);
                 for axis_id in 0..axis_state.mask_len {
                     if ffi::XIMaskIsSet(&mask, axis_id) {
                         let axis_value = unsafe{*axis_state.values.offset(axis_count)};
                         let delta = calc_scroll_deltas(event_data, axis_id, axis_value, &self.axis_list,
                                                        &mut self.current_state.axis_values);
-                        scroll_delta.0 += delta.0;
-                        scroll_delta.1 += delta.1;
+                        if let Some((source, delta)) = delta {
+                            scroll_source = Some(source);
+                            scroll_delta.0 += delta.0;
+                            scroll_delta.1 += delta.1;
+                        }
                         axis_count += 1;
                     }
                 }
 
-                if scroll_delta.0.abs() > 0.0 || scroll_delta.1.abs() > 0.0 {
-                    Some(MouseWheel(LineDelta(scroll_delta.0 as f32, scroll_delta.1 as f32),
-                                    TouchPhase::Moved))
-                } else {
-                    let new_cursor_pos = (event_data.event_x, event_data.event_y);
-                    if new_cursor_pos != self.current_state.cursor_pos {
-                        self.current_state.cursor_pos = new_cursor_pos;
-                        Some(MouseMoved((new_cursor_pos.0 as i32, new_cursor_pos.1 as i32)))
-                    } else {
-                        None
+                let is_scrolling = scroll_delta.0.abs() > 0.0 || scroll_delta.1.abs() > 0.0;
+                match scroll_source {
+                    Some(ScrollSource::Wheel) if is_scrolling => {
+                        translated_events.push(MouseWheel(LineDelta(scroll_delta.0 as f32, scroll_delta.1 as f32),
+                                                          TouchPhase::Moved));
+                    },
+                    Some(ScrollSource::Touchpad) if is_scrolling => {
+                        let (ended, phase) = self.scroll_sequence_phase(event_data.sourceid, event_data.time);
+                        translated_events.extend(ended);
+                        translated_events.push(MouseWheel(PixelDelta(scroll_delta.0 as f32, scroll_delta.1 as f32),
+                                                          phase));
+                    },
+                    _ => {
+                        // the fingers moved the pointer instead of scrolling, so
+                        // the scroll sequence of this device is over
+                        translated_events.extend(self.end_scroll_sequence(event_data.sourceid));
+
+                        let new_cursor_pos = (event_data.event_x, event_data.event_y);
+                        if new_cursor_pos != self.current_state.cursor_pos {
+                            self.current_state.cursor_pos = new_cursor_pos;
+                            translated_events.push(MouseMoved((new_cursor_pos.0 as i32, new_cursor_pos.1 as i32)));
+                        }
                     }
                 }
             },
@@ -254,14 +510,25 @@ impl XInputEventHandler {
                 // our window however, so clear the previous axis state whenever
                 // the cursor re-enters the window
                 self.current_state.axis_values.clear();
-                None
             },
-            ffi::XI_Leave => None,
-            ffi::XI_FocusIn => Some(Focused(true)),
-            ffi::XI_FocusOut => Some(Focused(false)),
+            ffi::XI_Leave => {
+                translated_events.extend(self.end_all_scroll_sequences());
+
+                // we won't hear about the tools anymore until they come back
+                let location = self.current_state.cursor_pos;
+                for pen in self.pen_list.iter_mut().filter(|pen| pen.in_proximity) {
+                    pen.in_proximity = false;
+                    translated_events.push(pen_event(pen, PenPhase::ProximityOut, location));
+                }
+            },
+            ffi::XI_FocusIn => translated_events.push(Focused(true)),
+            ffi::XI_FocusOut => {
+                translated_events.extend(self.end_all_scroll_sequences());
+                translated_events.push(Focused(false));
+            },
             ffi::XI_TouchBegin | ffi::XI_TouchUpdate | ffi::XI_TouchEnd => {
                 if !self.multitouch {
-                    return None
+                    return translated_events
                 }
                 let event_data: &ffi::XIDeviceEvent = unsafe{mem::transmute(cookie.data)};
                 let phase = match cookie.evtype {
@@ -270,33 +537,300 @@ impl XInputEventHandler {
                     ffi::XI_TouchEnd => TouchPhase::Ended,
                     _ => unreachable!()
                 };
-                Some(Event::Touch(Touch {
+                let touches = &mut self.current_state.touches;
+                let index = touches.iter().position(|t| {
+                    t.device_id == event_data.sourceid && t.touch_id == event_data.detail
+                });
+                let index = match index {
+                    // a touch id can't be reused before its end, unless we missed it
+                    Some(index) if phase != TouchPhase::Started => index,
+                    _ => {
+                        touches.retain(|t| t.device_id != event_data.sourceid || t.touch_id != event_data.detail);
+                        touches.push(TouchValues {
+                            device_id: event_data.sourceid,
+                            touch_id: event_data.detail,
+                            pressure: None,
+                            major: None,
+                            minor: None,
+                            orientation: None,
+                        });
+                        touches.len() - 1
+                    }
+                };
+                if let Some(device) = self.touch_list.iter().find(|d| d.device_id == event_data.sourceid) {
+                    update_touch_values(device, &event_data.valuators, &mut touches[index]);
+                }
+                let (force, contact) = touch_force_and_contact(&touches[index]);
+                if phase == TouchPhase::Ended {
+                    touches.remove(index);
+                }
+                translated_events.push(Event::Touch(Touch {
                     phase: phase,
                     location: (event_data.event_x, event_data.event_y),
                     id: event_data.detail as u64,
-                }))
+                    force: force,
+                    contact: contact,
+                    device_id: Some(event_data.sourceid as u64),
+                }));
+            }
+            ffi::XI_GesturePinchBegin | ffi::XI_GesturePinchUpdate | ffi::XI_GesturePinchEnd => {
+                let event_data: &ffi::XIGesturePinchEvent = unsafe{mem::transmute(cookie.data)};
+                let phase = match cookie.evtype {
+                    ffi::XI_GesturePinchBegin => TouchPhase::Started,
+                    ffi::XI_GesturePinchUpdate => TouchPhase::Moved,
+                    _ if event_data.flags & ffi::XIGesturePinchEventCancelled != 0 => TouchPhase::Cancelled,
+                    _ => TouchPhase::Ended
+                };
+                // the server reports the scale since the beginning of the gesture
+                if phase == TouchPhase::Started {
+                    self.current_state.pinch_scale = 1.0;
+                }
+                let scale_delta = if self.current_state.pinch_scale > 0.0 {
+                    event_data.scale / self.current_state.pinch_scale
+                } else {
+                    1.0
+                };
+                self.current_state.pinch_scale = event_data.scale;
+                translated_events.push(Event::Gesture(Gesture {
+                    kind: GestureKind::Pinch,
+                    phase: phase,
+                    fingers: event_data.detail as u32,
+                    scale_delta: scale_delta,
+                    rotation_delta: event_data.delta_angle,
+                    translation_delta: (event_data.delta_x, event_data.delta_y),
+                }));
+            }
+            ffi::XI_GestureSwipeBegin | ffi::XI_GestureSwipeUpdate | ffi::XI_GestureSwipeEnd => {
+                let event_data: &ffi::XIGestureSwipeEvent = unsafe{mem::transmute(cookie.data)};
+                let phase = match cookie.evtype {
+                    ffi::XI_GestureSwipeBegin => TouchPhase::Started,
+                    ffi::XI_GestureSwipeUpdate => TouchPhase::Moved,
+                    _ if event_data.flags & ffi::XIGestureSwipeEventCancelled != 0 => TouchPhase::Cancelled,
+                    _ => TouchPhase::Ended
+                };
+                translated_events.push(Event::Gesture(Gesture {
+                    kind: GestureKind::Swipe,
+                    phase: phase,
+                    fingers: event_data.detail as u32,
+                    scale_delta: 1.0,
+                    rotation_delta: 0.0,
+                    translation_delta: (event_data.delta_x, event_data.delta_y),
+                }));
+            }
+            _ => {}
+        }
+
+        translated_events
+    }
+
+    /// Updates the state of the pen that caused a motion or button event, and returns
+    /// the pen events to deliver. Returns nothing if the event doesn't come from a pen.
+    fn translate_pen_event(&mut self, evtype: libc::c_int, event_data: &ffi::XIDeviceEvent) -> Vec<Event> {
+        let mut events = Vec::new();
+
+        let pen = match self.pen_list.iter_mut().find(|pen| pen.device_id == event_data.sourceid) {
+            Some(pen) => pen,
+            None => return events
+        };
+        let location = (event_data.event_x, event_data.event_y);
+
+        if let Some(value) = valuator_value(&event_data.valuators, pen.pressure.number) {
+            pen.last_pressure = pen.pressure.normalize(value);
+        }
+        if let Some(axis) = pen.tilt_x {
+            if let Some(value) = valuator_value(&event_data.valuators, axis.number) {
+                pen.last_tilt.0 = axis.normalize_centered(value);
             }
+        }
+        if let Some(axis) = pen.tilt_y {
+            if let Some(value) = valuator_value(&event_data.valuators, axis.number) {
+                pen.last_tilt.1 = axis.normalize_centered(value);
+            }
+        }
+        if let Some(axis) = pen.distance {
+            if let Some(value) = valuator_value(&event_data.valuators, axis.number) {
+                pen.last_distance = Some(axis.normalize(value));
+            }
+        }
+
+        if !pen.in_proximity {
+            pen.in_proximity = true;
+            events.push(pen_event(pen, PenPhase::ProximityIn, location));
+        }
+
+        let state = if evtype == ffi::XI_ButtonPress {
+            ElementState::Pressed
+        } else {
+            ElementState::Released
+        };
+        let phase = match (evtype, event_data.detail) {
+            (ffi::XI_Motion, _) => PenPhase::Moved,
+            // the tip is reported as the first button
+            (ffi::XI_ButtonPress, 1) => PenPhase::Down,
+            (ffi::XI_ButtonRelease, 1) => {
+                pen.last_pressure = 0.0;
+                PenPhase::Up
+            },
+            (_, 2) => PenPhase::Button(state, 1),
+            (_, 3) => PenPhase::Button(state, 2),
+            // buttons 4 to 7 are reserved for scrolling
+            (_, button) if button >= 8 => PenPhase::Button(state, (button - 5) as u8),
+            _ => return events
+        };
+        events.push(pen_event(pen, phase, location));
+        events
+    }
+
+    /// Records a scroll delta from a touchpad and returns the phase to report it with.
+    ///
+    /// If the previous sequence of the device timed out, the event that ends it is
+    /// returned as well.
+    fn scroll_sequence_phase(&mut self, device_id: i32, time: ffi::Time) -> (Option<Event>, TouchPhase) {
+        self.current_state.scroll_clock = Some((time, Instant::now()));
+        let (timed_out, phase) = self.current_state.scroll_sequences.record(device_id, time);
+        (if timed_out { Some(scroll_ended_event()) } else { None }, phase)
+    }
+
+    /// Ends the scroll sequence of the given device, if any.
+    fn end_scroll_sequence(&mut self, device_id: i32) -> Option<Event> {
+        if self.current_state.scroll_sequences.end(device_id) {
+            Some(scroll_ended_event())
+        } else {
+            None
+        }
+    }
+
+    /// Ends the scroll sequences of all devices.
+    fn end_all_scroll_sequences(&mut self) -> Vec<Event> {
+        let count = self.current_state.scroll_sequences.end_all();
+        (0..count).map(|_| scroll_ended_event()).collect()
+    }
+
+    /// Ends the scroll sequences that didn't receive any delta in time, which must be checked
+    /// while waiting for events since no event tells that the fingers were lifted.
+    pub fn end_timed_out_scroll_sequences(&mut self) -> Vec<Event> {
+        let now = match self.server_time() {
+            Some(now) => now,
+            None => return Vec::new()
+        };
+        let count = self.current_state.scroll_sequences.end_timed_out(now);
+        (0..count).map(|_| scroll_ended_event()).collect()
+    }
+
+    /// Returns how long to wait for events before a scroll sequence times out, or `None` if
+    /// there is no scroll sequence.
+    pub fn scroll_sequence_timeout(&self) -> Option<Duration> {
+        match (self.current_state.scroll_sequences.next_timeout(), self.server_time()) {
+            (Some(timeout), Some(now)) => Some(Duration::from_millis(timeout.saturating_sub(now) as u64)),
             _ => None
         }
     }
+
+    /// Estimates the current server time from the time of the latest scroll delta.
+    fn server_time(&self) -> Option<ffi::Time> {
+        self.current_state.scroll_clock.map(|(time, received)| {
+            let elapsed = received.elapsed();
+            time + (elapsed.as_secs() * 1000 + elapsed.subsec_nanos() as u64 / 1_000_000) as ffi::Time
+        })
+    }
+}
+
+fn pen_event(pen: &PenDevice, phase: PenPhase, location: (f64, f64)) -> Event {
+    Event::Pen(Pen {
+        phase: phase,
+        tool: pen.tool,
+        location: location,
+        pressure: pen.last_pressure,
+        tilt: pen.last_tilt,
+        distance: pen.last_distance,
+        id: pen.device_id as u64,
+    })
+}
+
+/// Updates the last-seen values of a touch with the valuators of an event.
+fn update_touch_values(device: &TouchDevice, valuators: &ffi::XIValuatorState, touch: &mut TouchValues) {
+    let value = |axis: Option<ValuatorRange>| {
+        axis.and_then(|axis| valuator_value(valuators, axis.number).map(|value| (axis, value)))
+    };
+
+    if let Some((axis, value)) = value(device.pressure) {
+        touch.pressure = Some(axis.normalize(value));
+    }
+    if let Some((_, value)) = value(device.touch_major) {
+        touch.major = Some(value);
+    }
+    if let Some((_, value)) = value(device.touch_minor) {
+        touch.minor = Some(value);
+    }
+    // the kernel maps the valuator range to a quarter of a turn
+    if let Some((axis, value)) = value(device.orientation) {
+        touch.orientation = Some(axis.normalize_centered(value) * 90.0);
+    }
+}
+
+/// Returns the pressure and the contact ellipse of a touch.
+fn touch_force_and_contact(touch: &TouchValues) -> (Option<f64>, Option<ContactEllipse>) {
+    let contact = touch.major.map(|major| {
+        ContactEllipse {
+            major: major,
+            minor: touch.minor.unwrap_or(major),
+            orientation: touch.orientation.unwrap_or(0.0),
+        }
+    });
+
+    (touch.pressure, contact)
+}
+
+/// Returns the value of the given valuator if the event contains it.
+fn valuator_value(valuators: &ffi::XIValuatorState, number: i32) -> Option<f64> {
+    if number >= valuators.mask_len * 8 {
+        return None;
+    }
+    let mask = unsafe{ from_raw_parts(valuators.mask, valuators.mask_len as usize) };
+    if !ffi::XIMaskIsSet(&mask, number) {
+        return None;
+    }
+    // values are packed, only the valuators set in the mask are present
+    let index = (0..number).filter(|&i| ffi::XIMaskIsSet(&mask, i)).count();
+    Some(unsafe{ *valuators.values.offset(index as isize) })
 }
 
-fn read_input_axis_info(display: &Arc<XConnection>) -> Vec<Axis> {
+fn scroll_ended_event() -> Event {
+    Event::MouseWheel(MouseScrollDelta::PixelDelta(0.0, 0.0), TouchPhase::Ended)
+}
+
+fn read_input_device_info(display: &Arc<XConnection>) -> (Vec<Axis>, Vec<PenDevice>, Vec<TouchDevice>) {
     let mut axis_list = Vec::new();
+    let mut pen_list = Vec::new();
+    let mut touch_list = Vec::new();
     let mut device_count = 0;
 
-    // Check all input devices for scroll axes.
+    // Check all input devices for scroll axes, pen and touch valuators.
     let devices = unsafe{
         (display.xinput2.XIQueryDevice)(display.display, ffi::XIAllDevices, &mut device_count)
     };
     for i in 0..device_count {
         let device = unsafe { *(devices.offset(i as isize)) };
+        let scroll_source = if is_touchpad(display, &device) {
+            ScrollSource::Touchpad
+        } else {
+            ScrollSource::Wheel
+        };
+        let mut has_touch_class = false;
+        let mut pressure = None;
+        let mut tilt_x = None;
+        let mut tilt_y = None;
+        let mut distance = None;
+        let mut touch_pressure = None;
+        let mut touch_major = None;
+        let mut touch_minor = None;
+        let mut orientation = None;
         for k in 0..device.num_classes {
             let class = unsafe { *(device.classes.offset(k as isize)) };
             match unsafe { (*class)._type } {
                 // Note that scroll axis
                 // are reported both as 'XIScrollClass' and 'XIValuatorClass'
-                // axes. For the moment we only care about scrolling axes.
+                // axes.
                 ffi::XIScrollClass => {
                     let scroll_class: &ffi::XIScrollClassInfo = unsafe{mem::transmute(class)};
                     axis_list.push(Axis{
@@ -309,28 +843,195 @@ fn read_input_axis_info(display: &Arc<XConnection>) -> Vec<Axis> {
                             _ => { unreachable!() }
                         },
                         scroll_increment: scroll_class.increment,
+                        scroll_source: scroll_source,
                     })
                 },
+                ffi::XIValuatorClass => {
+                    let valuator_class: &ffi::XIValuatorClassInfo = unsafe{mem::transmute(class)};
+                    let range = ValuatorRange {
+                        number: valuator_class.number,
+                        min: valuator_class.min,
+                        max: valuator_class.max,
+                    };
+                    // the labels are the ones of xserver-properties.h
+                    match atom_name(display, valuator_class.label).as_ref().map(|s| &s[..]) {
+                        Some("Abs Pressure") => pressure = Some(range),
+                        Some("Abs Tilt X") => tilt_x = Some(range),
+                        Some("Abs Tilt Y") => tilt_y = Some(range),
+                        Some("Abs Distance") => distance = Some(range),
+                        Some("Abs MT Pressure") => touch_pressure = Some(range),
+                        Some("Abs MT Touch Major") => touch_major = Some(range),
+                        Some("Abs MT Touch Minor") => touch_minor = Some(range),
+                        Some("Abs MT Orientation") => orientation = Some(range),
+                        _ => {}
+                    }
+                },
+                ffi::XITouchClass => has_touch_class = true,
                 _ => {}
             }
         }
+
+        // touchscreens may report pressure as well, but they are handled as touches. The
+        // drivers create one device per tool
+        if let (ffi::XISlavePointer, false, Some(pressure)) = (device._use, has_touch_class, pressure) {
+            pen_list.push(PenDevice {
+                device_id: device.deviceid,
+                tool: pen_tool(display, &device),
+                pressure: pressure,
+                tilt_x: tilt_x,
+                tilt_y: tilt_y,
+                distance: distance,
+                in_proximity: false,
+                last_pressure: 0.0,
+                last_tilt: (0.0, 0.0),
+                last_distance: None,
+            });
+        }
+
+        if has_touch_class {
+            touch_list.push(TouchDevice {
+                device_id: device.deviceid,
+                pressure: touch_pressure,
+                touch_major: touch_major,
+                touch_minor: touch_minor,
+                orientation: orientation,
+            });
+        }
     }
 
     unsafe {
         (display.xinput2.XIFreeDeviceInfo)(devices);
     }
 
-    axis_list
+    (axis_list, pen_list, touch_list)
+}
+
+fn atom_name(display: &Arc<XConnection>, atom: ffi::Atom) -> Option<String> {
+    use std::ffi::CStr;
+
+    if atom == 0 {
+        return None;
+    }
+    unsafe {
+        let name = (display.xlib.XGetAtomName)(display.display, atom);
+        if name.is_null() {
+            return None;
+        }
+        let result = CStr::from_ptr(name).to_string_lossy().into_owned();
+        (display.xlib.XFree)(name as *mut _);
+        Some(result)
+    }
+}
+
+/// Returns the lowercase name of a device.
+fn device_name(device: &ffi::XIDeviceInfo) -> String {
+    use std::ffi::CStr;
+
+    if device.name.is_null() {
+        return String::new();
+    }
+    unsafe { CStr::from_ptr(device.name) }.to_string_lossy().to_lowercase()
+}
+
+/// Returns the items of a property of an input device, or `None` if the device doesn't have it.
+fn device_property(display: &Arc<XConnection>, device_id: i32, name: &str) -> Option<Vec<u32>> {
+    let name = CString::new(name).unwrap();
+    let property = unsafe { (display.xlib.XInternAtom)(display.display, name.as_ptr(), ffi::True) };
+    if property == 0 {
+        // no device has the property
+        return None;
+    }
+
+    let mut property_type = 0;
+    let mut format = 0;
+    let mut items = 0;
+    let mut bytes_after = 0;
+    let mut data: *mut libc::c_uchar = ::std::ptr::null_mut();
+    let status = unsafe {
+        (display.xinput2.XIGetProperty)(display.display, device_id, property, 0, 64, ffi::False,
+                                        ffi::AnyPropertyType as ffi::Atom, &mut property_type,
+                                        &mut format, &mut items, &mut bytes_after, &mut data)
+    };
+    if status != ffi::Success as libc::c_int || data.is_null() {
+        return None;
+    }
+
+    // unlike window properties, the 32 bits items aren't stored in longs
+    let values = unsafe {
+        match format {
+            8 => from_raw_parts(data as *const u8, items as usize).iter().map(|&v| v as u32).collect(),
+            16 => from_raw_parts(data as *const u16, items as usize).iter().map(|&v| v as u32).collect(),
+            32 => from_raw_parts(data as *const u32, items as usize).to_vec(),
+            _ => Vec::new()
+        }
+    };
+    unsafe { (display.xlib.XFree)(data as *mut _) };
+    Some(values)
+}
+
+/// Tells the eraser of a tablet from its pens.
+///
+/// The wacom driver gives the type of the tool in the "Wacom Tool Type" property. The libinput
+/// and evdev drivers have no such property, but name the eraser after it (for example "Wacom
+/// Intuos S Pen eraser"), so for them the name is used as a heuristic.
+fn pen_tool(display: &Arc<XConnection>, device: &ffi::XIDeviceInfo) -> PenTool {
+    let is_eraser = match device_property(display, device.deviceid, "Wacom Tool Type") {
+        Some(tool_type) => {
+            tool_type.first().and_then(|&atom| atom_name(display, atom as ffi::Atom))
+                             .map_or(false, |name| name == "ERASER")
+        },
+        None => device_name(device).contains("eraser")
+    };
+
+    if is_eraser { PenTool::Eraser } else { PenTool::Pen }
+}
+
+/// Guesses whether a device is a touchpad rather than a mouse, since XInput doesn't tell what
+/// a device is.
+///
+/// In order:
+///
+/// - the libinput driver only offers two-finger and edge scrolling for touchpads,
+/// - the synaptics driver only handles touchpads,
+/// - with the evdev driver, touchpads report dependent touches or say so in their name.
+///
+/// Trackpoints and trackballs that scroll while a button is held are continuous but reported as
+/// wheels, as are the evdev touchpads named after their vendor only, like "AlpsPS/2 ALPS
+/// GlidePoint" without multitouch.
+fn is_touchpad(display: &Arc<XConnection>, device: &ffi::XIDeviceInfo) -> bool {
+    // two-finger, edge, on-button-down
+    if let Some(methods) = device_property(display, device.deviceid, "libinput Scroll Methods Available") {
+        return methods.iter().take(2).any(|&available| available != 0);
+    }
+    if device_property(display, device.deviceid, "Synaptics Scrolling Distance").is_some() {
+        return true;
+    }
+
+    for k in 0..device.num_classes {
+        let class = unsafe { *(device.classes.offset(k as isize)) };
+        if unsafe { (*class)._type } == ffi::XITouchClass {
+            let touch_class: &ffi::XITouchClassInfo = unsafe{mem::transmute(class)};
+            if touch_class.mode == ffi::XIDependentTouch {
+                return true;
+            }
+        }
+    }
+
+    let name = device_name(device);
+    name.contains("touchpad") || name.contains("trackpad")
 }
 
 /// Given an input motion event for an axis and the previous
-/// state of the axes, return the horizontal/vertical
-/// scroll deltas
+/// state of the axes, return the source of the scroll and the
+/// horizontal/vertical scroll deltas, or `None` if the axis is
+/// not a scroll axis.
+///
+/// Wheel deltas are expressed in lines, touchpad deltas in pixels.
 fn calc_scroll_deltas(event: &ffi::XIDeviceEvent,
                      axis_id: i32,
                      axis_value: f64,
                      axis_list: &[Axis],
-                     prev_axis_values: &mut Vec<AxisValue>) -> (f64, f64) {
+                     prev_axis_values: &mut Vec<AxisValue>) -> Option<(ScrollSource, (f64, f64))> {
     let prev_value_pos = prev_axis_values.iter().position(|prev_axis| {
         prev_axis.device_id == event.sourceid &&
             prev_axis.axis_number == axis_id
@@ -351,18 +1052,69 @@ fn calc_scroll_deltas(event: &ffi::XIDeviceEvent,
         None => prev_axis_values.push(new_axis_value)
     }
 
-    let mut scroll_delta = (0.0, 0.0);
-
     for axis in axis_list.iter() {
         if axis.id == event.sourceid &&
             axis.axis_number == axis_id {
-                match axis.axis_type {
-                    AxisType::HorizontalScroll => scroll_delta.0 = delta / axis.scroll_increment,
-                    AxisType::VerticalScroll => scroll_delta.1 = delta / axis.scroll_increment
-                }
+                // the increment is the distance of one wheel click, in device units
+                let delta = match axis.scroll_source {
+                    ScrollSource::Wheel => delta / axis.scroll_increment,
+                    ScrollSource::Touchpad => delta / axis.scroll_increment * PIXELS_PER_SCROLL_INCREMENT,
+                };
+                return Some((axis.scroll_source, match axis.axis_type {
+                    AxisType::HorizontalScroll => (delta, 0.0),
+                    AxisType::VerticalScroll => (0.0, delta)
+                }));
             }
     }
 
-    scroll_delta
+    None
 }
 
+
+#[cfg(test)]
+mod tests {
+    use events::TouchPhase;
+    use super::{ScrollSequences, SCROLL_SEQUENCE_TIMEOUT};
+
+    #[test]
+    fn scroll_sequence_phases() {
+        let mut sequences = ScrollSequences::default();
+        assert_eq!(sequences.record(2, 1000), (false, TouchPhase::Started));
+        assert_eq!(sequences.record(2, 1010), (false, TouchPhase::Moved));
+        assert_eq!(sequences.record(2, 1010 + SCROLL_SEQUENCE_TIMEOUT), (false, TouchPhase::Moved));
+
+        // a delta arriving after the timeout starts a new sequence
+        assert_eq!(sequences.record(2, 1011 + 2 * SCROLL_SEQUENCE_TIMEOUT), (true, TouchPhase::Started));
+    }
+
+    #[test]
+    fn scroll_sequences_per_device() {
+        let mut sequences = ScrollSequences::default();
+        assert_eq!(sequences.record(2, 1000), (false, TouchPhase::Started));
+        assert_eq!(sequences.record(3, 1005), (false, TouchPhase::Started));
+        assert_eq!(sequences.record(2, 1010), (false, TouchPhase::Moved));
+
+        assert!(sequences.end(3));
+        assert!(!sequences.end(3));
+        assert_eq!(sequences.record(3, 1020), (false, TouchPhase::Started));
+        assert_eq!(sequences.end_all(), 2);
+        assert_eq!(sequences.end_all(), 0);
+    }
+
+    #[test]
+    fn scroll_sequence_timeout() {
+        let mut sequences = ScrollSequences::default();
+        assert_eq!(sequences.next_timeout(), None);
+
+        sequences.record(2, 1000);
+        sequences.record(3, 1050);
+        assert_eq!(sequences.next_timeout(), Some(1001 + SCROLL_SEQUENCE_TIMEOUT));
+
+        // nothing times out until the deadline
+        assert_eq!(sequences.end_timed_out(1000 + SCROLL_SEQUENCE_TIMEOUT), 0);
+        assert_eq!(sequences.end_timed_out(1001 + SCROLL_SEQUENCE_TIMEOUT), 1);
+        assert_eq!(sequences.next_timeout(), Some(1051 + SCROLL_SEQUENCE_TIMEOUT));
+        assert_eq!(sequences.end_timed_out(1051 + SCROLL_SEQUENCE_TIMEOUT), 1);
+        assert_eq!(sequences.next_timeout(), None);
+    }
+}
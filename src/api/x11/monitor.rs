@@ -1,7 +1,11 @@
 use std::collections::VecDeque;
 use std::sync::Arc;
+use std::ptr;
 
-use super::XConnection;
+use libc;
+
+use VideoMode;
+use super::{ffi, XConnection};
 use native_monitor::NativeMonitorId;
 
 #[derive(Clone)]
@@ -41,4 +45,39 @@ impl MonitorId {
         self.0.check_errors().expect("Failed to get monitor dimensions");
         (width as u32, height as u32)
     }
+
+    pub fn get_video_modes(&self) -> Vec<VideoMode> {
+        get_xf86_modes(&self.0, self.1 as libc::c_int).iter().map(video_mode_of).collect()
+    }
+}
+
+/// Returns the XF86VidMode modes of a screen, starting with the current one, or nothing if
+/// the extension is not available.
+pub fn get_xf86_modes(display: &XConnection, screen_id: libc::c_int) -> Vec<ffi::XF86VidModeModeInfo> {
+    unsafe {
+        let mut mode_num: libc::c_int = 0;
+        let mut modes: *mut *mut ffi::XF86VidModeModeInfo = ptr::null_mut();
+        if (display.xf86vmode.XF86VidModeGetAllModeLines)(display.display, screen_id, &mut mode_num, &mut modes) == 0 {
+            return Vec::new();
+        }
+
+        let result = (0 .. mode_num).map(|i| ptr::read(*modes.offset(i as isize) as *const _)).collect();
+        (display.xlib.XFree)(modes as *mut _);
+        result
+    }
+}
+
+pub fn video_mode_of(mode: &ffi::XF86VidModeModeInfo) -> VideoMode {
+    // the dot clock is in kHz
+    let pixels_per_frame = mode.htotal as u64 * mode.vtotal as u64;
+    let refresh_rate = if pixels_per_frame == 0 {
+        0
+    } else {
+        (mode.dotclock as u64 * 1000 + pixels_per_frame / 2) / pixels_per_frame
+    };
+
+    VideoMode {
+        dimensions: (mode.hdisplay as u32, mode.vdisplay as u32),
+        refresh_rate: refresh_rate as u32,
+    }
 }
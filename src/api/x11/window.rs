@@ -3,21 +3,29 @@ use CreationError;
 use CreationError::OsError;
 use libc;
 use std::borrow::Borrow;
-use std::{mem, ptr};
+use std::ffi::CStr;
+use std::{mem, ptr, slice};
 use std::cell::Cell;
 use std::sync::atomic::AtomicBool;
 use std::collections::VecDeque;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, Once, ONCE_INIT};
 use std::os::raw::c_long;
 use std::thread;
 use std::time::Duration;
 
 use CursorState;
+use Icon;
+use VideoMode;
 use WindowAttributes;
 
 use platform::MonitorId as PlatformMonitorId;
+use platform::PlatformSpecificWindowBuilderAttributes;
 
+use super::clipboard::Clipboard;
+use super::dnd::Dnd;
+use super::ime::{self, ImeState};
 use super::input::XInputEventHandler;
+use super::monitor;
 use super::{ffi};
 use super::{MonitorId, XConnection};
 
@@ -40,14 +48,22 @@ struct WindowProxyData {
 
 unsafe impl Send for WindowProxyData {}
 
+/// Video mode of a screen before it was switched for a fullscreen window.
+struct DesktopMode {
+    screen_id: libc::c_int,
+    mode: ffi::XF86VidModeModeInfo,
+}
+
 pub struct XWindow {
     display: Arc<XConnection>,
     window: ffi::Window,
-    is_fullscreen: bool,
-    screen_id: libc::c_int,
-    xf86_desk_mode: Option<ffi::XF86VidModeModeInfo>,
+    /// Mode of the desktop if we switched the video mode for fullscreen
+    desktop_mode: Mutex<Option<DesktopMode>>,
     ic: ffi::XIC,
     im: ffi::XIM,
+    ime_state: Arc<ImeState>,
+    clipboard: Clipboard,
+    dnd: Dnd,
     window_proxy_data: Arc<Mutex<Option<WindowProxyData>>>,
 }
 
@@ -66,11 +82,8 @@ impl Drop for XWindow {
 
             let _lock = GLOBAL_XOPENIM_LOCK.lock().unwrap();
 
-            if self.is_fullscreen {
-                if let Some(mut xf86_desk_mode) = self.xf86_desk_mode {
-                    (self.display.xf86vmode.XF86VidModeSwitchToMode)(self.display.display, self.screen_id, &mut xf86_desk_mode);
-                }
-                (self.display.xf86vmode.XF86VidModeSetViewPort)(self.display.display, self.screen_id, 0, 0);
+            if let Some(desktop_mode) = self.desktop_mode.lock().unwrap().take() {
+                switch_video_mode(&self.display, desktop_mode.screen_id, desktop_mode.mode);
             }
 
             (self.display.xlib.XDestroyIC)(self.ic);
@@ -80,6 +93,90 @@ impl Drop for XWindow {
     }
 }
 
+/// Returns the X screen of a monitor, or the default screen.
+fn screen_id_of(display: &XConnection, monitor: &PlatformMonitorId) -> libc::c_int {
+    match *monitor {
+        PlatformMonitorId::X(MonitorId(_, screen_id)) => screen_id as libc::c_int,
+        _ => unsafe { (display.xlib.XDefaultScreen)(display.display) },
+    }
+}
+
+/// Returns the mode of the screen that matches a video mode, the refresh rate being optional.
+fn find_xf86_mode(modes: &[ffi::XF86VidModeModeInfo], video_mode: &VideoMode)
+                  -> Option<ffi::XF86VidModeModeInfo>
+{
+    modes.iter()
+         .find(|m| {
+             let mode = monitor::video_mode_of(m);
+             mode.dimensions == video_mode.dimensions &&
+                 (video_mode.refresh_rate == 0 || mode.refresh_rate == video_mode.refresh_rate)
+         })
+         .cloned()
+}
+
+fn switch_video_mode(display: &XConnection, screen_id: libc::c_int, mut mode: ffi::XF86VidModeModeInfo) {
+    unsafe {
+        (display.xf86vmode.XF86VidModeSwitchToMode)(display.display, screen_id, &mut mode);
+        display.check_errors().expect("Failed to call XF86VidModeSwitchToMode");
+        (display.xf86vmode.XF86VidModeSetViewPort)(display.display, screen_id, 0, 0);
+        display.check_errors().expect("Failed to call XF86VidModeSetViewPort");
+    }
+}
+
+/// The purpose of a window, that the window manager uses to choose how to place and decorate it.
+///
+/// See `_NET_WM_WINDOW_TYPE` in the Extended Window Manager Hints.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WindowType {
+    /// A regular top-level window.
+    Normal,
+    /// A dialog window.
+    Dialog,
+    /// A small persistent window, such as a palette or a toolbox.
+    Utility,
+    /// A toolbar torn off from the main window.
+    Toolbar,
+    /// A splash screen displayed while the application is starting up.
+    Splash,
+    /// A menu torn off from the main window.
+    Menu,
+    /// A menu spawned from a menubar.
+    DropdownMenu,
+    /// A menu spawned from a right-click for example.
+    PopupMenu,
+    /// A tooltip.
+    Tooltip,
+    /// A notification, such as a "new mail" bubble.
+    Notification,
+    /// A dock or a panel, usually kept on top of the other windows.
+    Dock,
+}
+
+impl Default for WindowType {
+    #[inline]
+    fn default() -> WindowType {
+        WindowType::Normal
+    }
+}
+
+impl WindowType {
+    fn atom_name(&self) -> &'static str {
+        match *self {
+            WindowType::Normal => "_NET_WM_WINDOW_TYPE_NORMAL",
+            WindowType::Dialog => "_NET_WM_WINDOW_TYPE_DIALOG",
+            WindowType::Utility => "_NET_WM_WINDOW_TYPE_UTILITY",
+            WindowType::Toolbar => "_NET_WM_WINDOW_TYPE_TOOLBAR",
+            WindowType::Splash => "_NET_WM_WINDOW_TYPE_SPLASH",
+            WindowType::Menu => "_NET_WM_WINDOW_TYPE_MENU",
+            WindowType::DropdownMenu => "_NET_WM_WINDOW_TYPE_DROPDOWN_MENU",
+            WindowType::PopupMenu => "_NET_WM_WINDOW_TYPE_POPUP_MENU",
+            WindowType::Tooltip => "_NET_WM_WINDOW_TYPE_TOOLTIP",
+            WindowType::Notification => "_NET_WM_WINDOW_TYPE_NOTIFICATION",
+            WindowType::Dock => "_NET_WM_WINDOW_TYPE_DOCK",
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct WindowProxy {
     data: Arc<Mutex<Option<WindowProxyData>>>,
@@ -160,13 +257,31 @@ impl<'a> Iterator for PollEventsIterator<'a> {
             let res = unsafe { (xlib.XCheckMaskEvent)(self.window.x.display.display, -1, &mut xev) };
 
             if res == 0 {
-                let res = unsafe { (xlib.XCheckTypedEvent)(self.window.x.display.display, ffi::ClientMessage, &mut xev) };
-
-                if res == 0 {
-                    let res = unsafe { (xlib.XCheckTypedEvent)(self.window.x.display.display, ffi::GenericEvent, &mut xev) };
-                    if res == 0 {
+                // these events can't be selected with a mask
+                let unmaskable = [ffi::ClientMessage, ffi::GenericEvent, ffi::SelectionRequest, ffi::SelectionClear,
+                                  ffi::SelectionNotify];
+                let found = unmaskable.iter().any(|&event_type| unsafe {
+                    (xlib.XCheckTypedEvent)(self.window.x.display.display, event_type, &mut xev) != 0
+                });
+
+                if !found {
+                    // the end of a touchpad scroll is only noticed once no delta arrived in time
+                    let ended = self.window.input_handler.lock().unwrap().end_timed_out_scroll_sequences();
+                    if ended.is_empty() {
                         return None;
                     }
+                    self.window.pending_events.lock().unwrap().extend(ended);
+                    continue;
+                }
+            }
+
+            // key events are filtered while being translated
+            if xev.get_type() != ffi::KeyPress && xev.get_type() != ffi::KeyRelease {
+                let filtered = unsafe { (xlib.XFilterEvent)(&mut xev, 0) } == ffi::True;
+                let ime_events = self.window.x.ime_state.take_events();
+                self.window.pending_events.lock().unwrap().extend(ime_events);
+                if filtered {
+                    continue;
                 }
             }
 
@@ -182,7 +297,10 @@ impl<'a> Iterator for PollEventsIterator<'a> {
 
                     let client_msg: &ffi::XClientMessageEvent = unsafe { mem::transmute(&xev) };
 
-                    if client_msg.data.get_long(0) == self.window.wm_delete_window as libc::c_long {
+                    if self.window.x.dnd.is_dnd_message(client_msg) {
+                        let events = self.window.x.dnd.handle_message(client_msg);
+                        self.window.pending_events.lock().unwrap().extend(events);
+                    } else if client_msg.data.get_long(0) == self.window.wm_delete_window as libc::c_long {
                         self.window.is_closed.store(true, Relaxed);
                         return Some(Closed);
                     } else {
@@ -205,8 +323,36 @@ impl<'a> Iterator for PollEventsIterator<'a> {
                     return Some(Refresh);
                 },
 
+                ffi::SelectionRequest | ffi::SelectionClear => {
+                    self.window.x.clipboard.handle_event(&xev);
+                },
+
+                // the clipboard waits for its own answers, these are the files of a drop
+                ffi::SelectionNotify => {
+                    let notify: &ffi::XSelectionEvent = unsafe { mem::transmute(&xev) };
+                    let events = self.window.x.dnd.handle_selection_notify(notify, &self.window.x.clipboard);
+                    self.window.pending_events.lock().unwrap().extend(events);
+                },
+
+                ffi::PropertyNotify => {
+                    self.window.x.clipboard.handle_event(&xev);
+
+                    let property: &ffi::XPropertyEvent = unsafe { mem::transmute(&xev) };
+                    if property.window == self.window.x.window && property.atom == self.window.net_wm_state {
+                        let events = self.window.update_window_state();
+                        self.window.pending_events.lock().unwrap().extend(events);
+                    }
+                },
+
+                // only delivered while the pointer is grabbed by a drag, XInput2 reports them otherwise
+                ffi::MotionNotify | ffi::ButtonRelease => {
+                    let events = self.window.x.dnd.handle_pointer_event(&xev);
+                    self.window.pending_events.lock().unwrap().extend(events);
+                },
+
                 ffi::KeyPress | ffi::KeyRelease => {
                     let mut event: &mut ffi::XKeyEvent = unsafe { mem::transmute(&mut xev) };
+                    self.window.x.clipboard.update_time(event.time);
                     let events = self.window.input_handler.lock().unwrap().translate_key_event(&mut event);
                     for event in events {
                         self.window.pending_events.lock().unwrap().push_back(event);
@@ -215,14 +361,17 @@ impl<'a> Iterator for PollEventsIterator<'a> {
 
                 ffi::GenericEvent => {
                     if let Some(cookie) = GenericEventCookie::from_event(self.window.x.display.borrow(), xev) {
+                        // copying or pasting is usually triggered by a click
+                        if cookie.cookie.evtype == ffi::XI_ButtonPress || cookie.cookie.evtype == ffi::XI_ButtonRelease {
+                            let event_data: &ffi::XIDeviceEvent = unsafe { mem::transmute(cookie.cookie.data) };
+                            self.window.x.clipboard.update_time(event_data.time);
+                        }
                         match cookie.cookie.evtype {
-                            ffi::XI_DeviceChanged...ffi::XI_LASTEVENT => {
+                            ffi::XI_DeviceChanged...ffi::XI_GestureSwipeEnd => {
                                 match self.window.input_handler.lock() {
                                     Ok(mut handler) => {
-                                        match handler.translate_event(&cookie.cookie) {
-                                            Some(event) => self.window.pending_events.lock().unwrap().push_back(event),
-                                            None => {}
-                                        }
+                                        let events = handler.translate_event(&cookie.cookie);
+                                        self.window.pending_events.lock().unwrap().extend(events);
                                     },
                                     Err(_) => {}
                                 }
@@ -254,11 +403,17 @@ impl<'a> Iterator for WaitEventsIterator<'a> {
                 return Some(ev);
             }
 
-            // this will block until an event arrives, but doesn't remove
-            // it from the queue
-            let mut xev = unsafe { mem::uninitialized() };
-            unsafe { (self.window.x.display.xlib.XPeekEvent)(self.window.x.display.display, &mut xev) };
-            self.window.x.display.check_errors().expect("Failed to call XPeekEvent");
+            // a touchpad scroll in progress ends when no event arrives in time
+            let scroll_timeout = self.window.input_handler.lock().unwrap().scroll_sequence_timeout();
+            if let Some(timeout) = scroll_timeout {
+                self.window.x.display.wait_for_events(timeout);
+            } else {
+                // this will block until an event arrives, but doesn't remove
+                // it from the queue
+                let mut xev = unsafe { mem::uninitialized() };
+                unsafe { (self.window.x.display.xlib.XPeekEvent)(self.window.x.display.display, &mut xev) };
+                self.window.x.display.check_errors().expect("Failed to call XPeekEvent");
+            }
 
             // calling poll_events()
             if let Some(ev) = self.window.poll_events().next() {
@@ -270,64 +425,81 @@ impl<'a> Iterator for WaitEventsIterator<'a> {
     }
 }
 
+/// Size constraints sent to the window manager through `WM_NORMAL_HINTS`.
+struct SizeHints {
+    min_dimensions: Option<(u32, u32)>,
+    max_dimensions: Option<(u32, u32)>,
+    resizable: bool,
+    /// Size the window is kept at when it isn't resizable
+    size: (u32, u32),
+    resize_increments: Option<(u32, u32)>,
+    aspect_ratio: Option<(u32, u32)>,
+}
+
+impl SizeHints {
+    /// Converts the constraints to the `WM_NORMAL_HINTS` of the window.
+    fn to_x(&self) -> ffi::XSizeHints {
+        let mut size_hints: ffi::XSizeHints = unsafe { mem::zeroed() };
+
+        if let Some((width, height)) = self.min_dimensions {
+            size_hints.flags |= ffi::PMinSize;
+            size_hints.min_width = width as libc::c_int;
+            size_hints.min_height = height as libc::c_int;
+        }
+        if let Some((width, height)) = self.max_dimensions {
+            size_hints.flags |= ffi::PMaxSize;
+            size_hints.max_width = width as libc::c_int;
+            size_hints.max_height = height as libc::c_int;
+        }
+        if !self.resizable {
+            // window managers don't allow resizing when the minimum and maximum are equal
+            let (width, height) = self.size;
+            size_hints.flags |= ffi::PMinSize | ffi::PMaxSize;
+            size_hints.min_width = width as libc::c_int;
+            size_hints.min_height = height as libc::c_int;
+            size_hints.max_width = width as libc::c_int;
+            size_hints.max_height = height as libc::c_int;
+        }
+        if let Some((width, height)) = self.resize_increments {
+            size_hints.flags |= ffi::PResizeInc;
+            size_hints.width_inc = width as libc::c_int;
+            size_hints.height_inc = height as libc::c_int;
+        }
+        if let Some((width, height)) = self.aspect_ratio {
+            let ratio = ffi::AspectRatio { x: width as libc::c_int, y: height as libc::c_int };
+            size_hints.flags |= ffi::PAspect;
+            size_hints.min_aspect = ratio;
+            size_hints.max_aspect = ratio;
+        }
+
+        size_hints
+    }
+}
+
 pub struct Window {
     pub x: Arc<XWindow>,
     is_closed: AtomicBool,
     wm_delete_window: ffi::Atom,
+    net_wm_state: ffi::Atom,
     current_size: Cell<(libc::c_int, libc::c_int)>,
     /// Events that have been retreived with XLib but not dispatched with iterators yet
     pending_events: Mutex<VecDeque<Event>>,
     cursor_state: Mutex<CursorState>,
-    input_handler: Mutex<XInputEventHandler>
+    input_handler: Mutex<XInputEventHandler>,
+    size_hints: Mutex<SizeHints>,
+    /// Position and size to restore when leaving fullscreen
+    windowed_geometry: Mutex<Option<(i32, i32, u32, u32)>>,
+    /// Last known `(maximized, minimized)` state, to report the changes
+    window_state: Mutex<(bool, bool)>,
 }
 
 impl Window {
-    pub fn new(display: &Arc<XConnection>, window_attrs: &WindowAttributes)
+    pub fn new(display: &Arc<XConnection>, window_attrs: &WindowAttributes,
+               pl_attribs: &PlatformSpecificWindowBuilderAttributes)
                -> Result<Window, CreationError>
     {
         let dimensions = window_attrs.dimensions.unwrap_or((800, 600));
 
-        // not implemented
-        assert!(window_attrs.min_dimensions.is_none());
-        assert!(window_attrs.max_dimensions.is_none());
-
-        let screen_id = match window_attrs.monitor {
-            Some(PlatformMonitorId::X(MonitorId(_, monitor))) => monitor as i32,
-            _ => unsafe { (display.xlib.XDefaultScreen)(display.display) },
-        };
-
-        // finding the mode to switch to if necessary
-        let (mode_to_switch_to, xf86_desk_mode) = unsafe {
-            let mut mode_num: libc::c_int = mem::uninitialized();
-            let mut modes: *mut *mut ffi::XF86VidModeModeInfo = mem::uninitialized();
-            if (display.xf86vmode.XF86VidModeGetAllModeLines)(display.display, screen_id, &mut mode_num, &mut modes) == 0 {
-                (None, None)
-            } else {
-                let xf86_desk_mode: ffi::XF86VidModeModeInfo = ptr::read(*modes.offset(0));
-                let mode_to_switch_to = if window_attrs.monitor.is_some() {
-                    let matching_mode = (0 .. mode_num).map(|i| {
-                        let m: ffi::XF86VidModeModeInfo = ptr::read(*modes.offset(i as isize) as *const _); m
-                    }).find(|m| m.hdisplay == dimensions.0 as u16 && m.vdisplay == dimensions.1 as u16);
-                    if let Some(matching_mode) = matching_mode {
-                        Some(matching_mode)
-                    } else {
-                        let m = (0 .. mode_num).map(|i| {
-                            let m: ffi::XF86VidModeModeInfo = ptr::read(*modes.offset(i as isize) as *const _); m
-                        }).find(|m| m.hdisplay >= dimensions.0 as u16 && m.vdisplay >= dimensions.1 as u16);
-
-                        match m {
-                            Some(m) => Some(m),
-                            None => return Err(OsError(format!("Could not find a suitable graphics mode")))
-                        }
-                    }
-                } else {
-                    None
-                };
-                (display.xlib.XFree)(modes as *mut _);
-                (mode_to_switch_to, Some(xf86_desk_mode))
-            }
-        };
-
         // getting the root window
         let root = unsafe { (display.xlib.XDefaultRootWindow)(display.display) };
         display.check_errors().expect("Failed to get root window");
@@ -339,7 +511,7 @@ impl Window {
             swa.event_mask = ffi::ExposureMask | ffi::StructureNotifyMask |
                 ffi::VisibilityChangeMask | ffi::KeyPressMask | ffi::PointerMotionMask |
                 ffi::KeyReleaseMask | ffi::ButtonPressMask |
-                ffi::ButtonReleaseMask | ffi::KeymapStateMask;
+                ffi::ButtonReleaseMask | ffi::KeymapStateMask | ffi::PropertyChangeMask;
             swa.border_pixel = 0;
             if window_attrs.transparent {
                 swa.background_pixel = 0;
@@ -364,14 +536,56 @@ impl Window {
             win
         };
 
-        // set visibility
-        if window_attrs.visible {
+        // the window manager only reads the states of a window when mapping it, afterwards they
+        // must be changed through client messages
+        let initial_states: Vec<&str> = [
+            (pl_attribs.always_on_top, "_NET_WM_STATE_ABOVE"),
+            (pl_attribs.always_on_bottom, "_NET_WM_STATE_BELOW"),
+            (pl_attribs.skip_taskbar, "_NET_WM_STATE_SKIP_TASKBAR"),
+            (pl_attribs.skip_pager, "_NET_WM_STATE_SKIP_PAGER"),
+            (pl_attribs.sticky, "_NET_WM_STATE_STICKY"),
+        ].iter().filter(|&&(enabled, _)| enabled).map(|&(_, state)| state).collect();
+
+        if !initial_states.is_empty() {
+            let net_wm_state = display.get_atom("_NET_WM_STATE");
+            let states: Vec<ffi::Atom> = initial_states.iter().map(|state| display.get_atom(state)).collect();
+            display.check_errors().expect("Failed to call XInternAtom");
+
             unsafe {
-                (display.xlib.XMapRaised)(display.display, window);
-                (display.xlib.XFlush)(display.display);
+                (display.xlib.XChangeProperty)(display.display, window, net_wm_state, ffi::XA_ATOM, 32,
+                                               ffi::PropModeReplace, states.as_ptr() as *const libc::c_uchar,
+                                               states.len() as libc::c_int);
             }
+            display.check_errors().expect("Failed to set _NET_WM_STATE");
+        }
 
-            display.check_errors().expect("Failed to set window visibility");
+        // the window type is also only read by the window manager when mapping the window
+        unsafe {
+            let net_wm_window_type = display.get_atom("_NET_WM_WINDOW_TYPE");
+            let window_type = display.get_atom(pl_attribs.x11_window_type.atom_name());
+            display.check_errors().expect("Failed to call XInternAtom");
+
+            (display.xlib.XChangeProperty)(display.display, window, net_wm_window_type, ffi::XA_ATOM, 32,
+                                           ffi::PropModeReplace, &window_type as *const ffi::Atom as *const libc::c_uchar,
+                                           1);
+            display.check_errors().expect("Failed to set _NET_WM_WINDOW_TYPE");
+        }
+
+        // Set ICCCM WM_CLASS property, based on initial window title if no class was given. The
+        // ICCCM requires it before mapping the window, window managers may ignore later changes
+        unsafe {
+            let (instance, class) = match pl_attribs.class {
+                Some((ref instance, ref class)) => (&**instance, &**class),
+                None => (&*window_attrs.title, &*window_attrs.title),
+            };
+            with_c_str(instance, |c_instance| with_c_str(class, |c_class| {
+                let hint = (display.xlib.XAllocClassHint)();
+                (*hint).res_name = c_instance as *mut libc::c_char;
+                (*hint).res_class = c_class as *mut libc::c_char;
+                (display.xlib.XSetClassHint)(display.display, window, hint);
+                display.check_errors().expect("Failed to call XSetClassHint");
+                (display.xlib.XFree)(hint as *mut _);
+            }));
         }
 
         // creating window, step 2
@@ -392,6 +606,19 @@ impl Window {
         let im = unsafe {
             let _lock = GLOBAL_XOPENIM_LOCK.lock().unwrap();
 
+            // programs start with the C locale, which input methods don't support, so the first
+            // window uses the locale of the environment unless the application chose one already
+            static LOCALE_INIT: Once = ONCE_INIT;
+            LOCALE_INIT.call_once(|| {
+                let locale = libc::setlocale(libc::LC_CTYPE, ptr::null());
+                if locale.is_null() || [&b"C"[..], &b"POSIX"[..]].contains(&CStr::from_ptr(locale).to_bytes()) {
+                    libc::setlocale(libc::LC_CTYPE, b"\0".as_ptr() as *const _);
+                }
+            });
+
+            // use the input method selected with XMODIFIERS
+            (display.xlib.XSetLocaleModifiers)(b"\0".as_ptr() as *const _);
+
             let im = (display.xlib.XOpenIM)(display.display, ptr::null_mut(), ptr::null_mut(), ptr::null_mut());
             if im.is_null() {
                 return Err(OsError(format!("XOpenIM failed")));
@@ -400,16 +627,9 @@ impl Window {
         };
 
         // creating input context
+        let ime_state = Arc::new(ImeState::new());
         let ic = unsafe {
-            let ic = with_c_str("inputStyle", |input_style|
-                with_c_str("clientWindow", |client_window|
-                    (display.xlib.XCreateIC)(
-                        im, input_style,
-                        ffi::XIMPreeditNothing | ffi::XIMStatusNothing, client_window,
-                        window, ptr::null::<()>()
-                    )
-                )
-            );
+            let ic = ime::create_input_context(display, im, window, &ime_state);
             if ic.is_null() {
                 return Err(OsError(format!("XCreateIC failed")));
             }
@@ -427,83 +647,6 @@ impl Window {
             }
         }
 
-        // Set ICCCM WM_CLASS property based on initial window title
-        unsafe {
-            with_c_str(&*window_attrs.title, |c_name| {
-                let hint = (display.xlib.XAllocClassHint)();
-                (*hint).res_name = c_name as *mut libc::c_char;
-                (*hint).res_class = c_name as *mut libc::c_char;
-                (display.xlib.XSetClassHint)(display.display, window, hint);
-                display.check_errors().expect("Failed to call XSetClassHint");
-                (display.xlib.XFree)(hint as *mut _);
-            });
-        }
-
-        let is_fullscreen = window_attrs.monitor.is_some();
-
-        if is_fullscreen {
-            let state_atom = unsafe {
-                with_c_str("_NET_WM_STATE", |state|
-                    (display.xlib.XInternAtom)(display.display, state, 0)
-                )
-            };
-            display.check_errors().expect("Failed to call XInternAtom");
-            let fullscreen_atom = unsafe {
-                with_c_str("_NET_WM_STATE_FULLSCREEN", |state_fullscreen|
-                    (display.xlib.XInternAtom)(display.display, state_fullscreen, 0)
-                )
-            };
-            display.check_errors().expect("Failed to call XInternAtom");
-
-            let client_message_event = ffi::XClientMessageEvent {
-                type_: ffi::ClientMessage,
-                serial: 0,
-                send_event: 1,            // true because we are sending this through `XSendEvent`
-                display: display.display,
-                window: window,
-                message_type: state_atom, // the _NET_WM_STATE atom is sent to change the state of a window
-                format: 32,               // view `data` as `c_long`s
-                data: {
-                    let mut data = ffi::ClientMessageData::new();
-                    // This first `long` is the action; `1` means add/set following property.
-                    data.set_long(0, 1);
-                    // This second `long` is the property to set (fullscreen)
-                    data.set_long(1, fullscreen_atom as c_long);
-                    data
-                }
-            };
-            let mut x_event = ffi::XEvent::from(client_message_event);
-
-            unsafe {
-                (display.xlib.XSendEvent)(
-                    display.display,
-                    root,
-                    0,
-                    ffi::SubstructureRedirectMask | ffi::SubstructureNotifyMask,
-                    &mut x_event as *mut _
-                );
-                display.check_errors().expect("Failed to call XSendEvent");
-            }
-
-            if let Some(mut mode_to_switch_to) = mode_to_switch_to {
-                unsafe {
-                    (display.xf86vmode.XF86VidModeSwitchToMode)(
-                        display.display,
-                        screen_id,
-                        &mut mode_to_switch_to
-                    );
-                    display.check_errors().expect("Failed to call XF86VidModeSwitchToMode");
-                }
-            }
-            else {
-                println!("[glutin] Unexpected state: `mode` is None creating fullscreen window");
-            }
-            unsafe {
-                (display.xf86vmode.XF86VidModeSetViewPort)(display.display, screen_id, 0, 0);
-                display.check_errors().expect("Failed to call XF86VidModeSetViewPort");
-            }
-        }
-
         // creating the window object
         let window_proxy_data = WindowProxyData {
             display: display.clone(),
@@ -517,21 +660,58 @@ impl Window {
                 window: window,
                 im: im,
                 ic: ic,
-                screen_id: screen_id,
-                is_fullscreen: is_fullscreen,
-                xf86_desk_mode: xf86_desk_mode,
+                ime_state: ime_state.clone(),
+                clipboard: Clipboard::new(display, window),
+                dnd: Dnd::new(display, window),
+                desktop_mode: Mutex::new(None),
                 window_proxy_data: window_proxy_data,
             }),
             is_closed: AtomicBool::new(false),
             wm_delete_window: wm_delete_window,
+            net_wm_state: display.get_atom("_NET_WM_STATE"),
             current_size: Cell::new((0, 0)),
             pending_events: Mutex::new(VecDeque::new()),
             cursor_state: Mutex::new(CursorState::Normal),
-            input_handler: Mutex::new(XInputEventHandler::new(display, window, ic, window_attrs))
+            input_handler: Mutex::new(XInputEventHandler::new(display, window, ic, ime_state, window_attrs)),
+            size_hints: Mutex::new(SizeHints {
+                min_dimensions: window_attrs.min_dimensions,
+                max_dimensions: window_attrs.max_dimensions,
+                resizable: window_attrs.resizable,
+                size: dimensions,
+                resize_increments: window_attrs.resize_increments,
+                aspect_ratio: window_attrs.aspect_ratio,
+            }),
+            windowed_geometry: Mutex::new(None),
+            window_state: Mutex::new((false, false)),
         };
 
+        window.update_size_hints();
+        if !window_attrs.decorations {
+            window.set_decorations(false);
+        }
+        if let Some(ref icon) = window_attrs.window_icon {
+            window.set_window_icon(Some(icon));
+        }
         window.set_title(&window_attrs.title);
 
+        // the window manager reads the size hints, the decorations and the icon when mapping the
+        // window, so they must be set beforehand for the window to be created with them
+        if window_attrs.visible {
+            unsafe {
+                (display.xlib.XMapRaised)(display.display, window.x.window);
+                (display.xlib.XFlush)(display.display);
+            }
+
+            display.check_errors().expect("Failed to set window visibility");
+        }
+
+        if let Some(ref monitor) = window_attrs.monitor {
+            let screen_id = screen_id_of(display, monitor);
+            if let Err(err) = window.enter_fullscreen(screen_id, window_attrs.video_mode) {
+                return Err(OsError(err));
+            }
+        }
+
         if window_attrs.visible {
             unsafe {
                 let ref x_window: &XWindow = window.x.borrow();
@@ -588,6 +768,296 @@ impl Window {
 
     }
 
+    pub fn set_fullscreen(&self, monitor: Option<PlatformMonitorId>, video_mode: Option<VideoMode>) {
+        match monitor {
+            Some(monitor) => {
+                {
+                    let mut windowed_geometry = self.windowed_geometry.lock().unwrap();
+                    if windowed_geometry.is_none() {
+                        *windowed_geometry = self.get_geometry().map(|(x, y, w, h, _)| (x, y, w, h));
+                    }
+                }
+
+                // the window is fullscreen even if the video mode couldn't be changed
+                let screen_id = screen_id_of(&self.x.display, &monitor);
+                let _ = self.enter_fullscreen(screen_id, video_mode);
+            },
+
+            None => {
+                self.restore_desktop_mode();
+                self.change_net_wm_state(false, "_NET_WM_STATE_FULLSCREEN");
+
+                // window managers usually restore the geometry themselves, but the window was
+                // moved and the video mode change may have messed up the size
+                if let Some((x, y, width, height)) = self.windowed_geometry.lock().unwrap().take() {
+                    self.set_inner_size(width, height);
+                    self.set_position(x, y);
+                }
+            },
+        }
+    }
+
+    /// Makes the window fullscreen, and switches the screen to the video mode if there is one.
+    /// Without a video mode the window covers the screen at its current resolution.
+    fn enter_fullscreen(&self, screen_id: libc::c_int, video_mode: Option<VideoMode>) -> Result<(), String> {
+        let result = match video_mode {
+            Some(video_mode) => self.switch_to_video_mode(screen_id, video_mode),
+            None => {
+                // going from exclusive to borderless, the window stays where it is and the window
+                // manager makes it cover the monitor it is on
+                self.restore_desktop_mode();
+                Ok(())
+            }
+        };
+
+        self.change_net_wm_state(true, "_NET_WM_STATE_FULLSCREEN");
+        result
+    }
+
+    /// Switches a screen to a video mode, and moves the window to the part of the screen that
+    /// stays visible.
+    fn switch_to_video_mode(&self, screen_id: libc::c_int, video_mode: VideoMode) -> Result<(), String> {
+        let modes = monitor::get_xf86_modes(&self.x.display, screen_id);
+        let mode = match find_xf86_mode(&modes, &video_mode) {
+            Some(mode) => mode,
+            None => return Err(format!("The monitor doesn't support the {}x{} video mode",
+                                       video_mode.dimensions.0, video_mode.dimensions.1))
+        };
+
+        {
+            let mut desktop_mode = self.x.desktop_mode.lock().unwrap();
+            // when moving to another screen, the previous one gets its mode back
+            let restore = match *desktop_mode {
+                Some(ref saved) => saved.screen_id != screen_id,
+                None => false
+            };
+            if restore {
+                let saved = desktop_mode.take().unwrap();
+                switch_video_mode(&self.x.display, saved.screen_id, saved.mode);
+            }
+            if desktop_mode.is_none() {
+                // the first mode is the current one
+                *desktop_mode = Some(DesktopMode { screen_id: screen_id, mode: modes[0] });
+            }
+        }
+
+        switch_video_mode(&self.x.display, screen_id, mode);
+
+        // each screen has its own root window, and the smaller video mode only shows its top-left
+        // corner, so the window must be at the origin of the root to cover the monitor
+        self.set_position(0, 0);
+        Ok(())
+    }
+
+    fn restore_desktop_mode(&self) {
+        if let Some(desktop_mode) = self.x.desktop_mode.lock().unwrap().take() {
+            switch_video_mode(&self.x.display, desktop_mode.screen_id, desktop_mode.mode);
+        }
+    }
+
+    pub fn set_maximized(&self, maximized: bool) {
+        // both in one message, so that the window manager maximizes the window in one go
+        self.change_net_wm_states(maximized, &["_NET_WM_STATE_MAXIMIZED_HORZ", "_NET_WM_STATE_MAXIMIZED_VERT"]);
+    }
+
+    pub fn set_minimized(&self, minimized: bool) {
+        if minimized {
+            unsafe {
+                let screen = (self.x.display.xlib.XDefaultScreen)(self.x.display.display);
+                (self.x.display.xlib.XIconifyWindow)(self.x.display.display, self.x.window, screen);
+                (self.x.display.xlib.XFlush)(self.x.display.display);
+            }
+            self.x.display.check_errors().expect("Failed to call XIconifyWindow");
+        } else {
+            self.show();
+        }
+    }
+
+    #[inline]
+    pub fn restore(&self) {
+        self.set_minimized(false);
+        self.set_maximized(false);
+    }
+
+    /// Reads the states that the window manager set on the window, and returns events for the
+    /// ones that changed.
+    fn update_window_state(&self) -> Vec<Event> {
+        let states = self.get_net_wm_state();
+        let display = &self.x.display;
+        let maximized = states.contains(&display.get_atom("_NET_WM_STATE_MAXIMIZED_HORZ")) &&
+                        states.contains(&display.get_atom("_NET_WM_STATE_MAXIMIZED_VERT"));
+        let minimized = states.contains(&display.get_atom("_NET_WM_STATE_HIDDEN"));
+        display.check_errors().expect("Failed to call XInternAtom");
+
+        let mut events = Vec::new();
+        let mut window_state = self.window_state.lock().unwrap();
+        if maximized != window_state.0 {
+            events.push(Event::Maximized(maximized));
+        }
+        if minimized != window_state.1 {
+            events.push(Event::Minimized(minimized));
+        }
+        *window_state = (maximized, minimized);
+        events
+    }
+
+    /// Returns the states in the `_NET_WM_STATE` property of the window.
+    fn get_net_wm_state(&self) -> Vec<ffi::Atom> {
+        let display = &self.x.display;
+        let mut property_type = 0;
+        let mut format = 0;
+        let mut items = 0;
+        let mut bytes_after = 0;
+        let mut data: *mut libc::c_uchar = ptr::null_mut();
+
+        unsafe {
+            (display.xlib.XGetWindowProperty)(display.display, self.x.window, self.net_wm_state,
+                                              0, c_long::max_value() / 4, ffi::False, ffi::XA_ATOM,
+                                              &mut property_type, &mut format, &mut items,
+                                              &mut bytes_after, &mut data);
+        }
+        display.check_errors().expect("Failed to call XGetWindowProperty");
+
+        if data.is_null() {
+            return Vec::new();
+        }
+        let states = unsafe { slice::from_raw_parts(data as *const ffi::Atom, items as usize).to_vec() };
+        unsafe { (display.xlib.XFree)(data as *mut _) };
+        states
+    }
+
+    #[inline]
+    pub fn set_always_on_top(&self, always_on_top: bool) {
+        self.change_net_wm_state(always_on_top, "_NET_WM_STATE_ABOVE");
+    }
+
+    #[inline]
+    pub fn set_always_on_bottom(&self, always_on_bottom: bool) {
+        self.change_net_wm_state(always_on_bottom, "_NET_WM_STATE_BELOW");
+    }
+
+    #[inline]
+    pub fn set_skip_taskbar(&self, skip_taskbar: bool) {
+        self.change_net_wm_state(skip_taskbar, "_NET_WM_STATE_SKIP_TASKBAR");
+    }
+
+    #[inline]
+    pub fn set_skip_pager(&self, skip_pager: bool) {
+        self.change_net_wm_state(skip_pager, "_NET_WM_STATE_SKIP_PAGER");
+    }
+
+    #[inline]
+    pub fn set_sticky(&self, sticky: bool) {
+        self.change_net_wm_state(sticky, "_NET_WM_STATE_STICKY");
+    }
+
+    /// Adds or removes a state of the `_NET_WM_STATE` property by asking the window manager.
+    #[inline]
+    fn change_net_wm_state(&self, add: bool, state: &str) {
+        self.change_net_wm_states(add, &[state])
+    }
+
+    /// Adds or removes one or two states of the `_NET_WM_STATE` property at once, which is all
+    /// a single message can hold.
+    fn change_net_wm_states(&self, add: bool, states: &[&str]) {
+        assert!(states.len() == 1 || states.len() == 2);
+        let display = &self.x.display;
+        let state_atom = display.get_atom("_NET_WM_STATE");
+        let atoms: Vec<ffi::Atom> = states.iter().map(|state| display.get_atom(state)).collect();
+        display.check_errors().expect("Failed to call XInternAtom");
+
+        let client_message_event = ffi::XClientMessageEvent {
+            type_: ffi::ClientMessage,
+            serial: 0,
+            send_event: 1,            // true because we are sending this through `XSendEvent`
+            display: display.display,
+            window: self.x.window,
+            message_type: state_atom, // the _NET_WM_STATE atom is sent to change the state of a window
+            format: 32,               // view `data` as `c_long`s
+            data: {
+                let mut data = ffi::ClientMessageData::new();
+                // This first `long` is the action; `1` means add/set and `0` means remove.
+                data.set_long(0, add as c_long);
+                // The second and third `long`s are the properties to change, `0` for none
+                data.set_long(1, atoms[0] as c_long);
+                data.set_long(2, atoms.get(1).map(|&atom| atom as c_long).unwrap_or(0));
+                data
+            }
+        };
+        let mut x_event = ffi::XEvent::from(client_message_event);
+
+        unsafe {
+            let root = (display.xlib.XDefaultRootWindow)(display.display);
+            (display.xlib.XSendEvent)(
+                display.display,
+                root,
+                0,
+                ffi::SubstructureRedirectMask | ffi::SubstructureNotifyMask,
+                &mut x_event as *mut _
+            );
+            (display.xlib.XFlush)(display.display);
+        }
+        display.check_errors().expect("Failed to call XSendEvent");
+    }
+
+    pub fn set_decorations(&self, decorations: bool) {
+        // flags, functions, decorations, input mode, status
+        const MWM_HINTS_DECORATIONS: libc::c_long = 1 << 1;
+        let hints: [libc::c_long; 5] = [MWM_HINTS_DECORATIONS, 0, decorations as libc::c_long, 0, 0];
+
+        let motif_wm_hints = self.x.display.get_atom("_MOTIF_WM_HINTS");
+        self.x.display.check_errors().expect("Failed to call XInternAtom");
+
+        unsafe {
+            (self.x.display.xlib.XChangeProperty)(self.x.display.display, self.x.window,
+                                                  motif_wm_hints, motif_wm_hints, 32,
+                                                  ffi::PropModeReplace,
+                                                  hints.as_ptr() as *const libc::c_uchar,
+                                                  hints.len() as libc::c_int);
+            (self.x.display.xlib.XFlush)(self.x.display.display);
+        }
+        self.x.display.check_errors().expect("Failed to set _MOTIF_WM_HINTS");
+    }
+
+    pub fn set_window_icon(&self, icon: Option<&Icon>) {
+        let net_wm_icon = self.x.display.get_atom("_NET_WM_ICON");
+        self.x.display.check_errors().expect("Failed to call XInternAtom");
+
+        let icon = match icon {
+            Some(icon) => icon,
+            None => {
+                unsafe {
+                    (self.x.display.xlib.XDeleteProperty)(self.x.display.display, self.x.window, net_wm_icon);
+                    (self.x.display.xlib.XFlush)(self.x.display.display);
+                }
+                self.x.display.check_errors().expect("Failed to call XDeleteProperty");
+                return;
+            }
+        };
+
+        // for each image its width, its height and its pixels as ARGB, each in a 32 bits item
+        // that xlib stores in a long
+        let mut data: Vec<libc::c_ulong> = Vec::new();
+        for image in &icon.images {
+            data.push(image.width as libc::c_ulong);
+            data.push(image.height as libc::c_ulong);
+            data.extend(image.rgba.chunks(4).map(|p| {
+                ((p[3] as libc::c_ulong) << 24) | ((p[0] as libc::c_ulong) << 16) |
+                    ((p[1] as libc::c_ulong) << 8) | p[2] as libc::c_ulong
+            }));
+        }
+
+        unsafe {
+            (self.x.display.xlib.XChangeProperty)(self.x.display.display, self.x.window,
+                                                  net_wm_icon, ffi::XA_CARDINAL, 32,
+                                                  ffi::PropModeReplace,
+                                                  data.as_ptr() as *const libc::c_uchar,
+                                                  data.len() as libc::c_int);
+            (self.x.display.xlib.XFlush)(self.x.display.display);
+        }
+        self.x.display.check_errors().expect("Failed to set _NET_WM_ICON");
+    }
+
     pub fn show(&self) {
         unsafe {
             (self.x.display.xlib.XMapRaised)(self.x.display.display, self.x.window);
@@ -649,10 +1119,43 @@ impl Window {
 
     #[inline]
     pub fn set_inner_size(&self, x: u32, y: u32) {
+        let resizable = {
+            let mut hints = self.size_hints.lock().unwrap();
+            hints.size = (x, y);
+            hints.resizable
+        };
+        // the window manager would refuse the new size otherwise
+        if !resizable {
+            self.update_size_hints();
+        }
+
         unsafe { (self.x.display.xlib.XResizeWindow)(self.x.display.display, self.x.window, x as libc::c_uint, y as libc::c_uint); }
         self.x.display.check_errors().expect("Failed to call XResizeWindow");
     }
 
+    #[inline]
+    pub fn set_min_dimensions(&self, dimensions: Option<(u32, u32)>) {
+        self.size_hints.lock().unwrap().min_dimensions = dimensions;
+        self.update_size_hints();
+    }
+
+    #[inline]
+    pub fn set_max_dimensions(&self, dimensions: Option<(u32, u32)>) {
+        self.size_hints.lock().unwrap().max_dimensions = dimensions;
+        self.update_size_hints();
+    }
+
+    /// Sends the size constraints to the window manager, which enforces them.
+    fn update_size_hints(&self) {
+        let mut size_hints = self.size_hints.lock().unwrap().to_x();
+
+        unsafe {
+            (self.x.display.xlib.XSetWMNormalHints)(self.x.display.display, self.x.window, &mut size_hints);
+            (self.x.display.xlib.XFlush)(self.x.display.display);
+        }
+        self.x.display.check_errors().expect("Failed to call XSetWMNormalHints");
+    }
+
     #[inline]
     pub fn create_window_proxy(&self) -> WindowProxy {
         WindowProxy {
@@ -830,4 +1333,106 @@ impl Window {
             self.x.display.check_errors().map_err(|_| ())
         }
     }
+
+    #[inline]
+    pub fn get_clipboard(&self, mime_type: &str) -> Option<Vec<u8>> {
+        self.x.clipboard.get(mime_type)
+    }
+
+    #[inline]
+    pub fn set_clipboard(&self, mime_type: &str, data: Vec<u8>) {
+        self.x.clipboard.set(mime_type, data)
+    }
+
+    #[inline]
+    pub fn get_primary_selection(&self, mime_type: &str) -> Option<Vec<u8>> {
+        self.x.clipboard.get_primary(mime_type)
+    }
+
+    #[inline]
+    pub fn set_primary_selection(&self, mime_type: &str, data: Vec<u8>) {
+        self.x.clipboard.set_primary(mime_type, data)
+    }
+
+    pub fn start_drag(&self, contents: Vec<(String, Vec<u8>)>) {
+        let events = self.x.dnd.start_drag(contents, &self.x.clipboard);
+        self.pending_events.lock().unwrap().extend(events);
+    }
+
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        let was_composing = self.x.ime_state.is_composing();
+        self.input_handler.lock().unwrap().set_ime_allowed(allowed);
+        self.x.display.check_errors().expect("Failed to change the input context focus");
+
+        let mut pending_events = self.pending_events.lock().unwrap();
+        pending_events.extend(self.x.ime_state.take_events());
+        if !allowed && was_composing && self.x.ime_state.is_composing() {
+            // the input method didn't end the composition itself
+            self.x.ime_state.cancel_composition();
+            pending_events.push_back(Event::ImePreedit(String::new(), None));
+        }
+    }
+
+    pub fn set_ime_position(&self, x: i32, y: i32) {
+        unsafe {
+            ime::set_spot_location(&self.x.display, self.x.ic, x, y);
+            // input methods that don't draw next to the caret reject the position
+            self.x.display.ignore_error();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ffi, SizeHints};
+
+    fn hints() -> SizeHints {
+        SizeHints {
+            min_dimensions: None,
+            max_dimensions: None,
+            resizable: true,
+            size: (800, 600),
+            resize_increments: None,
+            aspect_ratio: None,
+        }
+    }
+
+    #[test]
+    fn size_hints_without_limits() {
+        assert_eq!(hints().to_x().flags, 0);
+    }
+
+    #[test]
+    fn size_hints_min_max() {
+        let size_hints = SizeHints { min_dimensions: Some((200, 100)), ..hints() }.to_x();
+        assert_eq!(size_hints.flags, ffi::PMinSize);
+        assert_eq!((size_hints.min_width, size_hints.min_height), (200, 100));
+
+        let size_hints = SizeHints { min_dimensions: Some((200, 100)), max_dimensions: Some((1000, 500)),
+                                     ..hints() }.to_x();
+        assert_eq!(size_hints.flags, ffi::PMinSize | ffi::PMaxSize);
+        assert_eq!((size_hints.min_width, size_hints.min_height), (200, 100));
+        assert_eq!((size_hints.max_width, size_hints.max_height), (1000, 500));
+    }
+
+    #[test]
+    fn size_hints_not_resizable() {
+        // the size overrides the limits so that the window manager can't resize the window
+        let size_hints = SizeHints { resizable: false, min_dimensions: Some((200, 100)), ..hints() }.to_x();
+        assert_eq!(size_hints.flags, ffi::PMinSize | ffi::PMaxSize);
+        assert_eq!((size_hints.min_width, size_hints.min_height), (800, 600));
+        assert_eq!((size_hints.max_width, size_hints.max_height), (800, 600));
+    }
+
+    #[test]
+    fn size_hints_increments_and_aspect_ratio() {
+        let size_hints = SizeHints { resize_increments: Some((8, 16)), ..hints() }.to_x();
+        assert_eq!(size_hints.flags, ffi::PResizeInc);
+        assert_eq!((size_hints.width_inc, size_hints.height_inc), (8, 16));
+
+        let size_hints = SizeHints { aspect_ratio: Some((16, 9)), ..hints() }.to_x();
+        assert_eq!(size_hints.flags, ffi::PAspect);
+        assert_eq!((size_hints.min_aspect.x, size_hints.min_aspect.y), (16, 9));
+        assert_eq!((size_hints.max_aspect.x, size_hints.max_aspect.y), (16, 9));
+    }
 }
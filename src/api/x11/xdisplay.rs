@@ -3,6 +3,7 @@ use std::fmt;
 use std::error::Error;
 use std::ffi::CString;
 use std::sync::Mutex;
+use std::time::Duration;
 
 use libc;
 
@@ -71,6 +72,35 @@ impl XConnection {
     pub fn ignore_error(&self) {
         *self.latest_error.lock().unwrap() = None;
     }
+
+    /// Waits until there are events to process or the timeout elapsed, returns false in the
+    /// latter case.
+    pub fn wait_for_events(&self, timeout: Duration) -> bool {
+        if unsafe { (self.xlib.XPending)(self.display) } != 0 {
+            return true;
+        }
+        self.wait_for_data(timeout)
+    }
+
+    /// Waits until the server sent something that xlib didn't read yet or the timeout elapsed,
+    /// returns false in the latter case.
+    pub fn wait_for_data(&self, timeout: Duration) -> bool {
+        unsafe {
+            let mut fd = libc::pollfd {
+                fd: (self.xlib.XConnectionNumber)(self.display),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let timeout = timeout.as_secs() * 1000 + timeout.subsec_nanos() as u64 / 1_000_000;
+            libc::poll(&mut fd, 1, timeout as libc::c_int) > 0
+        }
+    }
+
+    /// Returns the atom with the given name, creating it if it doesn't exist yet.
+    pub fn get_atom(&self, name: &str) -> ffi::Atom {
+        let name = CString::new(name).unwrap();
+        unsafe { (self.xlib.XInternAtom)(self.display, name.as_ptr(), ffi::False) }
+    }
 }
 
 impl Drop for XConnection {
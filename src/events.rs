@@ -11,12 +11,58 @@ pub enum Event {
     /// The window has been closed.
     Closed,
 
+    /// The window has been maximized, or restored from being maximized.
+    ///
+    /// The parameter is true if the window is now maximized.
+    Maximized(bool),
+
+    /// The window has been minimized, or restored from being minimized.
+    ///
+    /// The parameter is true if the window is now minimized.
+    Minimized(bool),
+
     /// A file has been dropped into the window.
+    ///
+    /// When several files are dropped at once, one event is emitted per file.
     DroppedFile(PathBuf),
 
+    /// A file is being dragged over the window.
+    ///
+    /// When several files are dragged at once, one event is emitted per file. The drag either
+    /// ends with `DroppedFile` or with `HoveredFileCancelled`.
+    HoveredFile(PathBuf),
+
+    /// The files that were being dragged over the window left it without being dropped.
+    HoveredFileCancelled,
+
+    /// A drag started with `Window::start_drag` was dropped on a target that accepted the data.
+    DragDropped,
+
+    /// A drag started with `Window::start_drag` ended without being accepted by a target, or
+    /// could not be started.
+    DragCancelled,
+
     /// The window received a unicode character.
     ReceivedCharacter(char),
 
+    /// The input method changed the text being composed.
+    ///
+    /// Contains the text being composed and the byte range of the cursor in it, or `None` if
+    /// the cursor should be hidden. An empty text means that the composition ended.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - On X11, only emitted when the input method supports the on-the-spot style and the
+    ///   locale, see the crate documentation about the locale.
+    /// - On Wayland, only emitted if the compositor supports the text input protocol.
+    /// - Not reported on the other platforms.
+    ImePreedit(String, Option<(usize, usize)>),
+
+    /// The input method committed some text.
+    ///
+    /// The same text is also delivered as `ReceivedCharacter` events.
+    ImeCommit(String),
+
     /// The window gained or lost focus.
     ///
     /// The parameter is true if the window has gained focus, and false if it has lost focus.
@@ -56,7 +102,17 @@ pub enum Event {
 
 
     /// Touch event has been received
-    Touch(Touch)
+    Touch(Touch),
+
+    /// An event from a pen or the stylus of a graphics tablet has been received.
+    ///
+    /// Pens also move the cursor, so the usual mouse events are still delivered.
+    Pen(Pen),
+
+    /// A touchpad gesture has been recognized by the system.
+    ///
+    /// Only reported on X11 and Wayland, see `Gesture`.
+    Gesture(Gesture),
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
@@ -83,13 +139,150 @@ pub enum TouchPhase {
 /// as previously received End event is a new finger and has nothing to do with an old one.
 ///
 /// Touch may be cancelled if for example window lost focus.
+///
+/// `force`, `contact` and `device_id` are only available if the device and the platform
+/// report them.
+///
+/// ## Platform-specific
+///
+/// - On X11, `force` and `contact` are read from the valuators of the touchscreen.
+/// - On Wayland, `force`, `contact` and `device_id` are always `None`. The protocol doesn't
+///   send the pressure of touches, and their shape is only sent from version 6 of `wl_touch`,
+///   which the version of `wayland-client` in use doesn't support.
+/// - They are always `None` on the other platforms.
 pub struct Touch {
     pub phase: TouchPhase,
     pub location: (f64,f64),
     /// unique identifier of a finger.
+    pub id: u64,
+    /// Pressure of the finger, between 0 and 1.
+    pub force: Option<f64>,
+    /// Shape of the area of the screen in contact with the finger.
+    pub contact: Option<ContactEllipse>,
+    /// Identifier of the device that produced the touch.
+    ///
+    /// Two touches with different device ids come from different touchscreens.
+    pub device_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Approximation of the area in contact with a finger.
+///
+/// Lengths are in device units, which are only meaningful when compared to each other. A
+/// palm usually has a much larger major axis than a fingertip.
+///
+/// ## Platform-specific
+///
+/// - Only reported on X11, see `Touch`.
+pub struct ContactEllipse {
+    /// Length of the major axis of the ellipse.
+    pub major: f64,
+    /// Length of the minor axis of the ellipse. Equal to `major` if the device only
+    /// reports the major axis.
+    pub minor: f64,
+    /// Angle of the major axis in degrees, clockwise from the vertical axis.
+    pub orientation: f64,
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum PenPhase {
+    /// The tool came close enough to the tablet to be tracked.
+    ProximityIn,
+    /// The tip of the tool touched the tablet.
+    Down,
+    /// The tool moved, or its pressure, tilt or distance changed.
+    Moved,
+    /// The tip of the tool was lifted from the tablet.
+    Up,
+    /// A barrel button of the tool changed state. Buttons are numbered from 1.
+    Button(ElementState, u8),
+    /// The tool went out of range of the tablet.
+    ProximityOut,
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum PenTool {
+    /// The drawing end of a stylus.
+    Pen,
+    /// The eraser end of a stylus, or a dedicated eraser.
+    ///
+    /// On X11 the drivers expose each tool as a separate device. The wacom driver tells which
+    /// one is the eraser, but with the libinput and evdev drivers the eraser is guessed from the
+    /// word `eraser` in the name of its device, so an eraser named otherwise is reported as a
+    /// `Pen`.
+    Eraser,
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Represents pen event
+///
+/// A tool entering the range of the tablet generates a `ProximityIn` event and leaving
+/// it generates a `ProximityOut` event, with `Down`, `Moved`, `Up` and `Button` events
+/// in between.
+///
+/// ## Platform-specific
+///
+/// - On X11, proximity is not reported by the server, so `ProximityIn` is generated with
+///   the first event of a tool over the window and `ProximityOut` when the cursor leaves
+///   the window. A tool lifted out of range above the window therefore only generates
+///   `ProximityOut` once the cursor moves out of the window.
+/// - On X11, the tool type may be deduced from the name of the device, see `PenTool::Eraser`.
+/// - On Wayland, only reported if the compositor supports the tablet protocol. The compositor
+///   sends the tilt in degrees, 60 degrees are reported as 1.
+/// - Not reported on the other platforms.
+pub struct Pen {
+    pub phase: PenPhase,
+    pub tool: PenTool,
+    pub location: (f64, f64),
+    /// Pressure of the tip, between 0 and 1. Always 0 when the tip is up.
+    pub pressure: f64,
+    /// Tilt of the tool along the horizontal and vertical axes of the tablet, between -1 and 1
+    /// where 1 is the largest tilt that the tablet can measure. The tablets don't report
+    /// which angle this is, it is usually around 60 degrees.
+    ///
+    /// `(0.0, 0.0)` if the tablet doesn't report tilt.
+    pub tilt: (f64, f64),
+    /// Distance between the tool and the tablet, between 0 and 1, if reported by the tablet.
+    pub distance: Option<f64>,
+    /// unique identifier of the tool.
     pub id: u64
 }
 
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum GestureKind {
+    /// Fingers moving towards or away from each other, or rotating around their center.
+    Pinch,
+    /// Fingers moving together in the same direction.
+    Swipe,
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Represents touchpad gesture event
+///
+/// Every gesture starts with a `Started` event and finishes with an `Ended` event, or
+/// a `Cancelled` event if the gesture turned out not to be one (for example because a
+/// finger was lifted early). There may be 0 or more `Moved` events in between.
+///
+/// All values are relative to the previous event of the same gesture.
+///
+/// ## Platform-specific
+///
+/// - On X11, only reported with a server supporting XInput 2.4.
+/// - On Wayland, only reported if the compositor supports the pointer gestures protocol.
+/// - Not reported on the other platforms.
+pub struct Gesture {
+    pub kind: GestureKind,
+    pub phase: TouchPhase,
+    /// Number of fingers on the touchpad.
+    pub fingers: u32,
+    /// Factor by which the distance between the fingers changed. Always `1.0` for swipes.
+    pub scale_delta: f64,
+    /// Rotation of the fingers in degrees, clockwise. Always `0.0` for swipes.
+    pub rotation_delta: f64,
+    /// Movement of the center of the fingers in pixels.
+    pub translation_delta: (f64, f64),
+}
+
 pub type ScanCode = u8;
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
@@ -22,6 +22,14 @@
 //!     the `HeadlessRendererBuilder` object.
 //!
 //! By default only `window` is enabled.
+//!
+//! # Text input on X11
+//!
+//! Input methods only work with a locale they support, while programs start with the `C`
+//! locale. If the `LC_CTYPE` category of the locale is still `C` or `POSIX` when the first
+//! window is created, it is set from the environment with `setlocale(LC_CTYPE, "")`. This is
+//! only done once, the locale is left alone when the next windows are created.
+//! Applications that need another locale must call `setlocale` before creating a window.
 
 #[macro_use]
 extern crate lazy_static;
@@ -59,6 +67,9 @@ extern crate x11_dl;
 #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "dragonfly", target_os = "openbsd"))]
 #[macro_use(wayland_env)]
 extern crate wayland_client;
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "dragonfly", target_os = "openbsd"))]
+#[macro_use]
+extern crate wayland_sys;
 
 pub use events::*;
 pub use window::{WindowBuilder, WindowProxy, PollEventsIterator, WaitEventsIterator};
@@ -205,6 +216,99 @@ pub enum CursorState {
     Grab,
 }
 
+/// A video mode that a monitor can be switched to, see `MonitorId::get_video_modes`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VideoMode {
+    /// Width and height of the mode in pixels.
+    pub dimensions: (u32, u32),
+
+    /// Refresh rate of the mode in hertz, or `0` if unknown.
+    pub refresh_rate: u32,
+}
+
+/// An icon for a window, made of one or more images of different sizes among which the platform
+/// picks the one best suited for the taskbar, the window switcher or the title bar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Icon {
+    images: Vec<IconImage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IconImage {
+    width: u32,
+    height: u32,
+    /// Pixels row by row, four bytes per pixel in RGBA order.
+    rgba: Vec<u8>,
+}
+
+impl Icon {
+    /// Builds an icon from one image of `width` by `height` pixels, given row by row with four
+    /// bytes per pixel in RGBA order.
+    #[inline]
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Icon, BadIcon> {
+        let mut icon = Icon { images: Vec::new() };
+        match icon.add_rgba(rgba, width, height) {
+            Ok(()) => Ok(icon),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Adds an image of another size to the icon, replacing the image of the same size if any.
+    pub fn add_rgba(&mut self, rgba: Vec<u8>, width: u32, height: u32) -> Result<(), BadIcon> {
+        if width == 0 || height == 0 {
+            return Err(BadIcon::ZeroDimensions);
+        }
+
+        let expected = (width as usize).checked_mul(height as usize).and_then(|pixels| pixels.checked_mul(4));
+        if expected != Some(rgba.len()) {
+            return Err(BadIcon::ByteCountMismatch {
+                width: width,
+                height: height,
+                byte_count: rgba.len(),
+            });
+        }
+
+        self.images.retain(|image| image.width != width || image.height != height);
+        self.images.push(IconImage { width: width, height: height, rgba: rgba });
+        Ok(())
+    }
+}
+
+/// Error that can happen while building an `Icon`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BadIcon {
+    /// The width or the height of the image is zero.
+    ZeroDimensions,
+
+    /// The number of bytes isn't `width * height * 4`.
+    ByteCountMismatch {
+        width: u32,
+        height: u32,
+        byte_count: usize,
+    },
+}
+
+impl std::fmt::Display for BadIcon {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match *self {
+            BadIcon::ZeroDimensions => formatter.write_str("The icon has a zero width or height"),
+            BadIcon::ByteCountMismatch { width, height, byte_count } => {
+                write!(formatter, "The icon is {}x{} pixels but has {} bytes of RGBA data instead of {}",
+                       width, height, byte_count, width as u64 * height as u64 * 4)
+            },
+        }
+    }
+}
+
+impl std::error::Error for BadIcon {
+    fn description(&self) -> &str {
+        match *self {
+            BadIcon::ZeroDimensions => "The icon has a zero width or height",
+            BadIcon::ByteCountMismatch { .. } => "The icon data doesn't match its dimensions",
+        }
+    }
+}
+
 /// Attributes to use when creating a window.
 #[derive(Clone)]
 pub struct WindowAttributes {
@@ -224,16 +328,46 @@ pub struct WindowAttributes {
     /// The default is `None`.
     pub max_dimensions: Option<(u32, u32)>,
 
+    /// Whether the user can resize the window. If this is `false`, the window keeps the size
+    /// it is created with or given with `set_inner_size`.
+    ///
+    /// The default is `true`.
+    pub resizable: bool,
+
+    /// If `Some`, the width and height of the window only change by multiples of these
+    /// increments when the user resizes it, starting from the minimum dimensions.
+    ///
+    /// The default is `None`.
+    pub resize_increments: Option<(u32, u32)>,
+
+    /// If `Some`, the ratio between the width and the height of the window is kept to
+    /// `width / height` when the user resizes it.
+    ///
+    /// The default is `None`.
+    pub aspect_ratio: Option<(u32, u32)>,
+
     /// If `Some`, the window will be in fullscreen mode with the given monitor.
     ///
     /// The default is `None`.
     pub monitor: Option<platform::MonitorId>,
 
+    /// If `Some` along with `monitor`, the monitor is switched to this video mode while the
+    /// window is fullscreen (exclusive fullscreen). Otherwise the window covers the monitor at
+    /// its current resolution (borderless fullscreen).
+    ///
+    /// The default is `None`.
+    pub video_mode: Option<VideoMode>,
+
     /// The title of the window in the title bar.
     ///
     /// The default is `"glutin window"`.
     pub title: String,
 
+    /// The icon of the window in the taskbar and the window switcher.
+    ///
+    /// The default is `None`.
+    pub window_icon: Option<Icon>,
+
     /// Whether the window should be immediately visible upon creation.
     ///
     /// The default is `true`.
@@ -262,8 +396,13 @@ impl Default for WindowAttributes {
             dimensions: None,
             min_dimensions: None,
             max_dimensions: None,
+            resizable: true,
+            resize_increments: None,
+            aspect_ratio: None,
             monitor: None,
+            video_mode: None,
             title: "glutin window".to_owned(),
+            window_icon: None,
             visible: true,
             transparent: false,
             decorations: true,
@@ -287,3 +426,44 @@ mod native_monitor {
         Unavailable
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{BadIcon, Icon};
+
+    #[test]
+    fn icon_from_rgba() {
+        let icon = Icon::from_rgba(vec![0; 2 * 3 * 4], 2, 3).unwrap();
+        assert_eq!(icon.images.len(), 1);
+        assert_eq!((icon.images[0].width, icon.images[0].height), (2, 3));
+    }
+
+    #[test]
+    fn icon_errors() {
+        assert_eq!(Icon::from_rgba(Vec::new(), 0, 16), Err(BadIcon::ZeroDimensions));
+        assert_eq!(Icon::from_rgba(Vec::new(), 16, 0), Err(BadIcon::ZeroDimensions));
+        assert_eq!(Icon::from_rgba(vec![0; 15], 2, 2),
+                   Err(BadIcon::ByteCountMismatch { width: 2, height: 2, byte_count: 15 }));
+        // the byte count overflows
+        assert_eq!(Icon::from_rgba(Vec::new(), u32::max_value(), u32::max_value()),
+                   Err(BadIcon::ByteCountMismatch { width: u32::max_value(), height: u32::max_value(),
+                                                    byte_count: 0 }));
+    }
+
+    #[test]
+    fn icon_add_rgba() {
+        let mut icon = Icon::from_rgba(vec![0; 16 * 16 * 4], 16, 16).unwrap();
+        icon.add_rgba(vec![0; 32 * 32 * 4], 32, 32).unwrap();
+        assert_eq!(icon.images.len(), 2);
+
+        // an image of the same size replaces the previous one
+        icon.add_rgba(vec![255; 16 * 16 * 4], 16, 16).unwrap();
+        assert_eq!(icon.images.len(), 2);
+        let image = icon.images.iter().find(|image| image.width == 16).unwrap();
+        assert!(image.rgba.iter().all(|&byte| byte == 255));
+
+        // a bad image leaves the icon untouched
+        assert!(icon.add_rgba(vec![0; 4], 16, 16).is_err());
+        assert_eq!(icon.images.len(), 2);
+    }
+}
@@ -5,6 +5,8 @@ use Window;
 use platform::Window as LinuxWindow;
 use WindowBuilder;
 
+pub use api::x11::WindowType as XWindowType;
+
 /// Additional methods on `Window` that are specific to Unix.
 pub trait WindowExt {
     /// Returns a pointer to the `Window` object of xlib that is used by this window.
@@ -34,6 +36,31 @@ pub trait WindowExt {
     ///
     /// The pointer will become invalid when the glutin `Window` is destroyed.
     fn get_wayland_display(&self) -> Option<*mut libc::c_void>;
+
+    /// Keeps the window above the other windows, like `WindowBuilderExt::with_always_on_top`.
+    ///
+    /// Only has an effect on X11.
+    fn set_always_on_top(&self, always_on_top: bool);
+
+    /// Keeps the window below the other windows, like `WindowBuilderExt::with_always_on_bottom`.
+    ///
+    /// Only has an effect on X11.
+    fn set_always_on_bottom(&self, always_on_bottom: bool);
+
+    /// Hides the window from the taskbar, like `WindowBuilderExt::with_skip_taskbar`.
+    ///
+    /// Only has an effect on X11.
+    fn set_skip_taskbar(&self, skip_taskbar: bool);
+
+    /// Hides the window from the pager, like `WindowBuilderExt::with_skip_pager`.
+    ///
+    /// Only has an effect on X11.
+    fn set_skip_pager(&self, skip_pager: bool);
+
+    /// Shows the window on all the workspaces, like `WindowBuilderExt::with_sticky`.
+    ///
+    /// Only has an effect on X11.
+    fn set_sticky(&self, sticky: bool);
 }
 
 impl WindowExt for Window {
@@ -68,12 +95,120 @@ impl WindowExt for Window {
             _ => None
         }
     }
+
+    #[inline]
+    fn set_always_on_top(&self, always_on_top: bool) {
+        if let LinuxWindow::X(ref w) = self.window {
+            w.set_always_on_top(always_on_top);
+        }
+    }
+
+    #[inline]
+    fn set_always_on_bottom(&self, always_on_bottom: bool) {
+        if let LinuxWindow::X(ref w) = self.window {
+            w.set_always_on_bottom(always_on_bottom);
+        }
+    }
+
+    #[inline]
+    fn set_skip_taskbar(&self, skip_taskbar: bool) {
+        if let LinuxWindow::X(ref w) = self.window {
+            w.set_skip_taskbar(skip_taskbar);
+        }
+    }
+
+    #[inline]
+    fn set_skip_pager(&self, skip_pager: bool) {
+        if let LinuxWindow::X(ref w) = self.window {
+            w.set_skip_pager(skip_pager);
+        }
+    }
+
+    #[inline]
+    fn set_sticky(&self, sticky: bool) {
+        if let LinuxWindow::X(ref w) = self.window {
+            w.set_sticky(sticky);
+        }
+    }
 }
 
 /// Additional methods on `WindowBuilder` that are specific to Unix.
+///
+/// The window states and the window type are hints for the window manager through
+/// `_NET_WM_STATE` and `_NET_WM_WINDOW_TYPE`, they only have an effect on X11.
 pub trait WindowBuilderExt {
+    /// Keeps the window above the other windows, for overlays for example.
+    fn with_always_on_top(self, always_on_top: bool) -> WindowBuilder;
+
+    /// Keeps the window below the other windows.
+    fn with_always_on_bottom(self, always_on_bottom: bool) -> WindowBuilder;
+
+    /// Hides the window from the taskbar.
+    fn with_skip_taskbar(self, skip_taskbar: bool) -> WindowBuilder;
+
+    /// Hides the window from the pager, which shows miniatures of the workspaces.
+    fn with_skip_pager(self, skip_pager: bool) -> WindowBuilder;
 
+    /// Shows the window on all the workspaces.
+    fn with_sticky(self, sticky: bool) -> WindowBuilder;
+
+    /// Sets the purpose of the window through `_NET_WM_WINDOW_TYPE`, so that splash screens,
+    /// dialogs, menus or tooltips are placed and decorated accordingly by the window manager.
+    ///
+    /// Defaults to `XWindowType::Normal`.
+    fn with_x11_window_type(self, x11_window_type: XWindowType) -> WindowBuilder;
+
+    /// Sets the identity of the application, that desktop files, window rules and taskbar
+    /// grouping rely on instead of the title.
+    ///
+    /// On X11 `instance` and `class` are the two parts of `WM_CLASS`, usually the name of the
+    /// executable and the name of the application. On Wayland `class` is the class of the shell
+    /// surface, which should be the name of the desktop file.
+    ///
+    /// Defaults to the initial title of the window.
+    fn with_class(self, instance: String, class: String) -> WindowBuilder;
 }
 
 impl WindowBuilderExt for WindowBuilder {
+    #[inline]
+    fn with_always_on_top(mut self, always_on_top: bool) -> WindowBuilder {
+        self.platform_specific.always_on_top = always_on_top;
+        self
+    }
+
+    #[inline]
+    fn with_always_on_bottom(mut self, always_on_bottom: bool) -> WindowBuilder {
+        self.platform_specific.always_on_bottom = always_on_bottom;
+        self
+    }
+
+    #[inline]
+    fn with_skip_taskbar(mut self, skip_taskbar: bool) -> WindowBuilder {
+        self.platform_specific.skip_taskbar = skip_taskbar;
+        self
+    }
+
+    #[inline]
+    fn with_skip_pager(mut self, skip_pager: bool) -> WindowBuilder {
+        self.platform_specific.skip_pager = skip_pager;
+        self
+    }
+
+    #[inline]
+    fn with_sticky(mut self, sticky: bool) -> WindowBuilder {
+        self.platform_specific.sticky = sticky;
+        self
+    }
+
+    #[inline]
+    fn with_x11_window_type(mut self, x11_window_type: XWindowType) -> WindowBuilder {
+        self.platform_specific.x11_window_type = x11_window_type;
+        self
+    }
+
+    #[inline]
+    fn with_class(mut self, instance: String, class: String) -> WindowBuilder {
+        self.platform_specific.class = Some((instance, class));
+        self
+    }
 }
@@ -16,9 +16,18 @@ use api::x11;
 use api::x11::XConnection;
 use api::x11::XError;
 use api::x11::XNotSupported;
+use api::x11::WindowType as XWindowType;
 
 #[derive(Default)]
-pub struct PlatformSpecificWindowBuilderAttributes;
+pub struct PlatformSpecificWindowBuilderAttributes {
+    pub always_on_top: bool,
+    pub always_on_bottom: bool,
+    pub skip_taskbar: bool,
+    pub skip_pager: bool,
+    pub sticky: bool,
+    pub x11_window_type: XWindowType,
+    pub class: Option<(String, String)>,
+}
 
 enum Backend {
     X(Arc<XConnection>),
@@ -126,6 +135,15 @@ impl MonitorId {
             &MonitorId::None => (800, 600),     // FIXME:
         }
     }
+
+    #[inline]
+    pub fn get_video_modes(&self) -> Vec<::VideoMode> {
+        match self {
+            &MonitorId::X(ref m) => m.get_video_modes(),
+            &MonitorId::Wayland(ref m) => m.get_video_modes(),
+            &MonitorId::None => Vec::new(),
+        }
+    }
 }
 
 
@@ -169,16 +187,16 @@ impl<'a> Iterator for WaitEventsIterator<'a> {
 
 impl Window {
     #[inline]
-    pub fn new(window: &WindowAttributes, _: &PlatformSpecificWindowBuilderAttributes)
+    pub fn new(window: &WindowAttributes, pl_attribs: &PlatformSpecificWindowBuilderAttributes)
                -> Result<Window, CreationError>
     {
         match *BACKEND {
             Backend::Wayland => {
-                wayland::Window::new(window).map(Window::Wayland)
+                wayland::Window::new(window, pl_attribs).map(Window::Wayland)
             },
 
             Backend::X(ref connec) => {
-                x11::Window::new(connec, window).map(Window::X)
+                x11::Window::new(connec, window, pl_attribs).map(Window::X)
             },
 
             Backend::Error(ref error) => {
@@ -196,6 +214,54 @@ impl Window {
         }
     }
 
+    #[inline]
+    pub fn set_fullscreen(&self, monitor: Option<MonitorId>, video_mode: Option<::VideoMode>) {
+        match self {
+            &Window::X(ref w) => w.set_fullscreen(monitor, video_mode),
+            &Window::Wayland(ref w) => w.set_fullscreen(monitor, video_mode)
+        }
+    }
+
+    #[inline]
+    pub fn set_maximized(&self, maximized: bool) {
+        match self {
+            &Window::X(ref w) => w.set_maximized(maximized),
+            &Window::Wayland(ref w) => w.set_maximized(maximized)
+        }
+    }
+
+    #[inline]
+    pub fn set_minimized(&self, minimized: bool) {
+        match self {
+            &Window::X(ref w) => w.set_minimized(minimized),
+            &Window::Wayland(ref w) => w.set_minimized(minimized)
+        }
+    }
+
+    #[inline]
+    pub fn restore(&self) {
+        match self {
+            &Window::X(ref w) => w.restore(),
+            &Window::Wayland(ref w) => w.restore()
+        }
+    }
+
+    #[inline]
+    pub fn set_decorations(&self, decorations: bool) {
+        match self {
+            &Window::X(ref w) => w.set_decorations(decorations),
+            &Window::Wayland(ref w) => w.set_decorations(decorations)
+        }
+    }
+
+    #[inline]
+    pub fn set_window_icon(&self, icon: Option<&::Icon>) {
+        match self {
+            &Window::X(ref w) => w.set_window_icon(icon),
+            &Window::Wayland(ref w) => w.set_window_icon(icon)
+        }
+    }
+
     #[inline]
     pub fn show(&self) {
         match self {
@@ -252,6 +318,22 @@ impl Window {
         }
     }
 
+    #[inline]
+    pub fn set_min_dimensions(&self, dimensions: Option<(u32, u32)>) {
+        match self {
+            &Window::X(ref w) => w.set_min_dimensions(dimensions),
+            &Window::Wayland(ref w) => w.set_min_dimensions(dimensions)
+        }
+    }
+
+    #[inline]
+    pub fn set_max_dimensions(&self, dimensions: Option<(u32, u32)>) {
+        match self {
+            &Window::X(ref w) => w.set_max_dimensions(dimensions),
+            &Window::Wayland(ref w) => w.set_max_dimensions(dimensions)
+        }
+    }
+
     #[inline]
     pub fn create_window_proxy(&self) -> WindowProxy {
         match self {
@@ -316,6 +398,62 @@ impl Window {
         }
     }
 
+    #[inline]
+    pub fn get_clipboard(&self, mime_type: &str) -> Option<Vec<u8>> {
+        match self {
+            &Window::X(ref w) => w.get_clipboard(mime_type),
+            &Window::Wayland(ref w) => w.get_clipboard(mime_type)
+        }
+    }
+
+    #[inline]
+    pub fn set_clipboard(&self, mime_type: &str, data: Vec<u8>) {
+        match self {
+            &Window::X(ref w) => w.set_clipboard(mime_type, data),
+            &Window::Wayland(ref w) => w.set_clipboard(mime_type, data)
+        }
+    }
+
+    #[inline]
+    pub fn get_primary_selection(&self, mime_type: &str) -> Option<Vec<u8>> {
+        match self {
+            &Window::X(ref w) => w.get_primary_selection(mime_type),
+            &Window::Wayland(ref w) => w.get_primary_selection(mime_type)
+        }
+    }
+
+    #[inline]
+    pub fn set_primary_selection(&self, mime_type: &str, data: Vec<u8>) {
+        match self {
+            &Window::X(ref w) => w.set_primary_selection(mime_type, data),
+            &Window::Wayland(ref w) => w.set_primary_selection(mime_type, data)
+        }
+    }
+
+    #[inline]
+    pub fn start_drag(&self, contents: Vec<(String, Vec<u8>)>) {
+        match self {
+            &Window::X(ref w) => w.start_drag(contents),
+            &Window::Wayland(ref w) => w.start_drag(contents)
+        }
+    }
+
+    #[inline]
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        match self {
+            &Window::X(ref w) => w.set_ime_allowed(allowed),
+            &Window::Wayland(ref w) => w.set_ime_allowed(allowed)
+        }
+    }
+
+    #[inline]
+    pub fn set_ime_position(&self, x: i32, y: i32) {
+        match self {
+            &Window::X(ref w) => w.set_ime_position(x, y),
+            &Window::Wayland(ref w) => w.set_ime_position(x, y)
+        }
+    }
+
     #[inline]
     pub fn platform_display(&self) -> *mut libc::c_void {
         match self {
@@ -4,7 +4,9 @@ use std::default::Default;
 use CreationError;
 use CursorState;
 use Event;
+use Icon;
 use MouseCursor;
+use VideoMode;
 use Window;
 use WindowAttributes;
 use native_monitor::NativeMonitorId;
@@ -12,13 +14,17 @@ use native_monitor::NativeMonitorId;
 use libc;
 use platform;
 
+/// MIME type under which text is exchanged with the clipboard, the platforms also translate it
+/// to their native names for UTF-8 text.
+const CLIPBOARD_TEXT_MIME_TYPE: &'static str = "text/plain;charset=utf-8";
+
 /// Object that allows you to build windows.
 pub struct WindowBuilder {
     /// The attributes to use to create the window.
     pub window: WindowAttributes,
 
     /// Platform-specific configuration.
-    platform_specific: platform::PlatformSpecificWindowBuilderAttributes,
+    pub(crate) platform_specific: platform::PlatformSpecificWindowBuilderAttributes,
 }
 
 impl WindowBuilder {
@@ -43,6 +49,13 @@ impl WindowBuilder {
     /// Sets a minimum dimension size for the window
     ///
     /// Width and height are in pixels.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - On X11 the limits are enforced by the window manager.
+    /// - On Wayland the compositor isn't told about the limits: the sizes it proposes when
+    ///   configuring the window are clamped on the client side, so an interactive resize can
+    ///   still show a size outside of them until the window is configured.
     #[inline]
     pub fn with_min_dimensions(mut self, width: u32, height: u32) -> WindowBuilder {
         self.window.min_dimensions = Some((width, height));
@@ -51,13 +64,43 @@ impl WindowBuilder {
 
     /// Sets a maximum dimension size for the window
     ///
-    /// Width and height are in pixels.
+    /// Width and height are in pixels. See `with_min_dimensions` for the platform-specific
+    /// behavior.
     #[inline]
     pub fn with_max_dimensions(mut self, width: u32, height: u32) -> WindowBuilder {
         self.window.max_dimensions = Some((width, height));
         self
     }
 
+    /// Sets whether the user can resize the window.
+    ///
+    /// Only has an effect on X11 and Wayland for now.
+    #[inline]
+    pub fn with_resizable(mut self, resizable: bool) -> WindowBuilder {
+        self.window.resizable = resizable;
+        self
+    }
+
+    /// Makes the size of the window change by steps when the user resizes it, for example to
+    /// fit a grid of characters.
+    ///
+    /// Width and height are in pixels. Only has an effect on X11 and Wayland for now.
+    #[inline]
+    pub fn with_resize_increments(mut self, width: u32, height: u32) -> WindowBuilder {
+        self.window.resize_increments = Some((width, height));
+        self
+    }
+
+    /// Keeps the ratio between the width and the height of the window, for example
+    /// `with_aspect_ratio(16, 9)`.
+    ///
+    /// Only has an effect on X11 and Wayland for now.
+    #[inline]
+    pub fn with_aspect_ratio(mut self, width: u32, height: u32) -> WindowBuilder {
+        self.window.aspect_ratio = Some((width, height));
+        self
+    }
+
     /// Requests a specific title for the window.
     #[inline]
     pub fn with_title(mut self, title: String) -> WindowBuilder {
@@ -65,13 +108,48 @@ impl WindowBuilder {
         self
     }
 
-    /// Requests fullscreen mode.
+    /// Sets the icon of the window in the taskbar and the window switcher.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Has no effect on Windows, OS X, Android, iOS and Emscripten.
+    /// - On X11, the icon is set through `_NET_WM_ICON`.
+    /// - Has no effect on Wayland, where the compositor takes the icon from the desktop file of
+    ///   the application.
+    #[inline]
+    pub fn with_window_icon(mut self, icon: Icon) -> WindowBuilder {
+        self.window.window_icon = Some(icon);
+        self
+    }
+
+    /// Requests borderless fullscreen mode, where the window covers the monitor without
+    /// changing its resolution.
     ///
     /// If you don't specify dimensions for the window, it will match the monitor's.
     #[inline]
     pub fn with_fullscreen(mut self, monitor: MonitorId) -> WindowBuilder {
         let MonitorId(monitor) = monitor;
         self.window.monitor = Some(monitor);
+        self.window.video_mode = None;
+        self
+    }
+
+    /// Requests exclusive fullscreen mode, where the monitor is switched to the given video
+    /// mode until the window leaves fullscreen or is destroyed.
+    ///
+    /// The mode must be one of the `MonitorId::get_video_modes` of the monitor, otherwise the
+    /// window can't be built. If you don't specify dimensions for the window, they will match
+    /// the mode's.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only implemented on X11 and Wayland for now, the other platforms behave like
+    ///   `with_fullscreen`.
+    #[inline]
+    pub fn with_exclusive_fullscreen(mut self, monitor: MonitorId, mode: VideoMode) -> WindowBuilder {
+        let MonitorId(monitor) = monitor;
+        self.window.monitor = Some(monitor);
+        self.window.video_mode = Some(mode);
         self
     }
 
@@ -110,7 +188,10 @@ impl WindowBuilder {
     pub fn build(mut self) -> Result<Window, CreationError> {
         // resizing the window to the dimensions of the monitor when fullscreen
         if self.window.dimensions.is_none() && self.window.monitor.is_some() {
-            self.window.dimensions = Some(self.window.monitor.as_ref().unwrap().get_dimensions())
+            self.window.dimensions = match self.window.video_mode {
+                Some(ref mode) => Some(mode.dimensions),
+                None => Some(self.window.monitor.as_ref().unwrap().get_dimensions())
+            };
         }
 
         // default dimensions
@@ -161,6 +242,105 @@ impl Window {
         self.window.set_title(title)
     }
 
+    /// Makes the window fullscreen on the given monitor, or turns it back into a normal window
+    /// with `None`.
+    ///
+    /// The window covers the monitor without changing its resolution (borderless fullscreen).
+    /// Leaving fullscreen restores the position and size the window had before, and the video
+    /// mode of the monitor if it was switched with `set_exclusive_fullscreen`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Has no effect on Windows, OS X, Android, iOS and Emscripten.
+    /// - On X11 each monitor is a screen of its own, which windows can't move to: the window
+    ///   covers the monitor it was created on.
+    /// - On Wayland, the decorations are hidden and the size limits ignored while the window
+    ///   is fullscreen. Wayland compositors place windows themselves, so the position isn't
+    ///   restored there.
+    #[inline]
+    pub fn set_fullscreen(&self, monitor: Option<MonitorId>) {
+        self.window.set_fullscreen(monitor.map(|MonitorId(monitor)| monitor), None)
+    }
+
+    /// Makes the window fullscreen on the given monitor and switches the monitor to one of its
+    /// `get_video_modes` (exclusive fullscreen).
+    ///
+    /// Use `set_fullscreen(None)` to leave fullscreen and restore the video mode. The window is
+    /// still made fullscreen if the video mode can't be switched to.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Has no effect on Windows, OS X, Android, iOS and Emscripten.
+    /// - On Wayland, the video mode is only a hint for the compositor.
+    #[inline]
+    pub fn set_exclusive_fullscreen(&self, monitor: MonitorId, mode: VideoMode) {
+        let MonitorId(monitor) = monitor;
+        self.window.set_fullscreen(Some(monitor), Some(mode))
+    }
+
+    /// Maximizes the window, or restores it from being maximized.
+    ///
+    /// A `Maximized` event is emitted once the state changed, which also happens when the
+    /// user maximizes the window.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Has no effect on Windows, OS X, Android, iOS and Emscripten.
+    /// - On Wayland, the window is maximized on the monitor it is on, and the event is only
+    ///   emitted once the compositor resized it. Leaving the maximized state is only known
+    ///   from this method, since the core protocol doesn't report it.
+    #[inline]
+    pub fn set_maximized(&self, maximized: bool) {
+        self.window.set_maximized(maximized)
+    }
+
+    /// Minimizes the window, or restores it from being minimized.
+    ///
+    /// A `Minimized` event is emitted once the state changed, which also happens when the
+    /// user minimizes the window.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Has no effect on Windows, OS X, Android, iOS and Emscripten.
+    /// - Has no effect on Wayland either, minimizing requires `xdg_toplevel` which we don't use
+    ///   yet.
+    #[inline]
+    pub fn set_minimized(&self, minimized: bool) {
+        self.window.set_minimized(minimized)
+    }
+
+    /// Restores the window from being minimized or maximized.
+    #[inline]
+    pub fn restore(&self) {
+        self.window.restore()
+    }
+
+    /// Adds or removes the borders and title bar of the window.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Has no effect on Windows, OS X, Android, iOS and Emscripten.
+    /// - On X11, depends on the window manager honoring `_MOTIF_WM_HINTS`.
+    /// - Has no effect on Wayland, where the decorations can only be chosen when the window is
+    ///   built.
+    #[inline]
+    pub fn set_decorations(&self, decorations: bool) {
+        self.window.set_decorations(decorations)
+    }
+
+    /// Changes the icon of the window in the taskbar and the window switcher, `None` removing it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Has no effect on Windows, OS X, Android, iOS and Emscripten.
+    /// - On X11, the icon is set through `_NET_WM_ICON`.
+    /// - Has no effect on Wayland, where the compositor takes the icon from the desktop file of
+    ///   the application.
+    #[inline]
+    pub fn set_window_icon(&self, icon: Option<Icon>) {
+        self.window.set_window_icon(icon.as_ref())
+    }
+
     /// Shows the window if it was hidden.
     ///
     /// ## Platform-specific
@@ -270,6 +450,34 @@ impl Window {
         self.window.set_inner_size(x, y)
     }
 
+    /// Sets a minimum dimension size for the window, or removes it with `None`.
+    ///
+    /// Width and height are in pixels.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Has no effect on OS X, Android, iOS and Emscripten.
+    /// - On X11 the limits are enforced by the window manager.
+    /// - On Wayland the limits are only applied on the client side, see `with_min_dimensions`.
+    #[inline]
+    pub fn set_min_dimensions(&self, dimensions: Option<(u32, u32)>) {
+        self.window.set_min_dimensions(dimensions)
+    }
+
+    /// Sets a maximum dimension size for the window, or removes it with `None`.
+    ///
+    /// Width and height are in pixels.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Has no effect on OS X, Android, iOS and Emscripten.
+    /// - On X11 the limits are enforced by the window manager.
+    /// - On Wayland the limits are only applied on the client side, see `with_max_dimensions`.
+    #[inline]
+    pub fn set_max_dimensions(&self, dimensions: Option<(u32, u32)>) {
+        self.window.set_max_dimensions(dimensions)
+    }
+
     /// Returns an iterator that poll for the next event in the window's events queue.
     /// Returns `None` if there is no event in the queue.
     ///
@@ -341,6 +549,151 @@ impl Window {
         self.window.set_cursor_position(x, y)
     }
 
+    /// Returns the text in the clipboard, or `None` if the clipboard doesn't contain text.
+    ///
+    /// Blocks like `get_clipboard`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Always returns `None` on Windows, OS X, Android, iOS and Emscripten.
+    #[inline]
+    pub fn get_clipboard_text(&self) -> Option<String> {
+        self.window.get_clipboard(CLIPBOARD_TEXT_MIME_TYPE)
+                   .and_then(|data| String::from_utf8(data).ok())
+    }
+
+    /// Puts some text in the clipboard.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Has no effect on Windows, OS X, Android, iOS and Emscripten.
+    #[inline]
+    pub fn set_clipboard_text(&self, text: &str) {
+        self.window.set_clipboard(CLIPBOARD_TEXT_MIME_TYPE, text.as_bytes().to_vec())
+    }
+
+    /// Returns the contents of the clipboard in the given MIME type, or `None` if they are not
+    /// available in this type.
+    ///
+    /// This blocks until the application that owns the clipboard sends the data, so it
+    /// shouldn't be called on every frame.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Always returns `None` on Windows, OS X, Android, iOS and Emscripten.
+    /// - On X11, the owner is first asked for the types it can provide, then for the data in
+    ///   one of them. Each request gives up after a second, so this blocks for up to two
+    ///   seconds if the owner doesn't answer.
+    /// - On Wayland, gives up if the data isn't sent within a second.
+    #[inline]
+    pub fn get_clipboard(&self, mime_type: &str) -> Option<Vec<u8>> {
+        self.window.get_clipboard(mime_type)
+    }
+
+    /// Puts some data of the given MIME type in the clipboard, replacing its previous contents.
+    ///
+    /// The data is served to other applications for as long as the window exists and nobody
+    /// else replaces it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Has no effect on Windows, OS X, Android, iOS and Emscripten.
+    /// - On Wayland, the compositor ignores the request if the window hasn't received any input
+    ///   yet.
+    #[inline]
+    pub fn set_clipboard(&self, mime_type: &str, data: Vec<u8>) {
+        self.window.set_clipboard(mime_type, data)
+    }
+
+    /// Returns the text of the primary selection, which is what gets pasted with a middle
+    /// click, or `None` if it doesn't contain text.
+    ///
+    /// Only available on X11 and Wayland, see `get_primary_selection`.
+    #[inline]
+    pub fn get_primary_selection_text(&self) -> Option<String> {
+        self.window.get_primary_selection(CLIPBOARD_TEXT_MIME_TYPE)
+                   .and_then(|data| String::from_utf8(data).ok())
+    }
+
+    /// Makes some text the primary selection, usually the text that the user just selected.
+    ///
+    /// Only available on X11 and Wayland, see `set_primary_selection`.
+    #[inline]
+    pub fn set_primary_selection_text(&self, text: &str) {
+        self.window.set_primary_selection(CLIPBOARD_TEXT_MIME_TYPE, text.as_bytes().to_vec())
+    }
+
+    /// Returns the contents of the primary selection in the given MIME type, blocking until
+    /// the application that owns it sends them like `get_clipboard`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Always returns `None` on Windows, OS X, Android, iOS and Emscripten.
+    /// - On Wayland, also returns `None` if the compositor doesn't support the primary
+    ///   selection protocol.
+    #[inline]
+    pub fn get_primary_selection(&self, mime_type: &str) -> Option<Vec<u8>> {
+        self.window.get_primary_selection(mime_type)
+    }
+
+    /// Makes some data of the given MIME type the primary selection.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Has no effect on Windows, OS X, Android, iOS and Emscripten.
+    /// - On Wayland, has no effect if the compositor doesn't support the primary selection
+    ///   protocol, and the compositor ignores the new selection if the window hasn't received
+    ///   any input yet.
+    #[inline]
+    pub fn set_primary_selection(&self, mime_type: &str, data: Vec<u8>) {
+        self.window.set_primary_selection(mime_type, data)
+    }
+
+    /// Starts dragging data out of the window, to other applications or to the window itself.
+    ///
+    /// `contents` holds the data in every MIME type that the targets can choose from, for
+    /// example `text/uri-list` to drag files. Must be called while a mouse button is pressed,
+    /// usually in reaction to the `MouseInput` event of the press or to the following motion.
+    /// The drag follows the pointer until the button is released, and ends with either a
+    /// `DragDropped` or a `DragCancelled` event.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Has no effect on Windows, OS X, Android, iOS and Emscripten.
+    /// - On Wayland the end of a drop isn't reported by the version of `wl_data_device` we use,
+    ///   so `DragDropped` is sent once the target asks for the data.
+    #[inline]
+    pub fn start_drag(&self, contents: Vec<(String, Vec<u8>)>) {
+        self.window.start_drag(contents)
+    }
+
+    /// Sets whether the input method can be used to compose text in this window.
+    ///
+    /// The input method is allowed by default. Disallowing it cancels the current
+    /// composition, after which key presses are reported without being intercepted by the
+    /// input method, which is useful for games that only need text input in a chat box.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Has no effect on Windows, OS X, Android, iOS and Emscripten.
+    /// - On Wayland, has no effect if the compositor doesn't support the text input protocol.
+    #[inline]
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        self.window.set_ime_allowed(allowed)
+    }
+
+    /// Sets the position of the caret in window coordinates, so that the input method can
+    /// place its candidate window next to it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Has no effect on Windows, OS X, Android, iOS and Emscripten.
+    /// - On Wayland, has no effect if the compositor doesn't support the text input protocol.
+    #[inline]
+    pub fn set_ime_position(&self, x: i32, y: i32) {
+        self.window.set_ime_position(x, y)
+    }
+
     /// Sets how glutin handles the cursor. See the documentation of `CursorState` for details.
     ///
     /// Has no effect on Android.
@@ -459,4 +812,15 @@ impl MonitorId {
         let &MonitorId(ref id) = self;
         id.get_dimensions()
     }
+
+    /// Returns the video modes that the monitor supports, for exclusive fullscreen.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only implemented on X11 and Wayland for now, the list is empty on other platforms.
+    #[inline]
+    pub fn get_video_modes(&self) -> Vec<VideoMode> {
+        let &MonitorId(ref id) = self;
+        id.get_video_modes()
+    }
 }